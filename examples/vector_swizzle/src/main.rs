@@ -36,19 +36,13 @@ impl Vec4 {
         }
 
         // Set
-        // We want to skip all conflicting combinations (set_xxyz...)
-        @for X in @components, Y in @components, Z in @components, W in @components {
-            @if
-                @X != @Y && @X != @Z && @X != @W
-                && @Y != @Z && @Y != @W
-                && @Z != @W
-            {
-                pub fn @[set_ @X @Y @Z @W](&mut self, value: Vec4) {
-                    self.@X = value.x;
-                    self.@Y = value.y;
-                    self.@Z = value.z;
-                    self.@W = value.w;
-                }
+        // Permutations skip all conflicting combinations (set_xxyz...)
+        @for [X, Y, Z, W] in @components.permutations(4) {
+            pub fn @[set_ @X @Y @Z @W](&mut self, value: Vec4) {
+                self.@X = value.x;
+                self.@Y = value.y;
+                self.@Z = value.z;
+                self.@W = value.w;
             }
         }
     }
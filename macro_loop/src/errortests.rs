@@ -0,0 +1,145 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+
+use crate::macro_loop_partial;
+
+#[test]
+fn test_partial_output() {
+    assert_eq!(
+        expand_partial(quote! {
+            struct A;
+            @bad
+            struct B;
+        }),
+        ["error: cannot find `bad`", "structA;", "structB;"]
+    );
+
+    assert_eq!(
+        expand_partial(quote! {
+            fn f() -> u8 {
+                @(1 + a)
+            }
+            fn g() {}
+        }),
+        [
+            "error: cannot apply `+` to int `1` and ident `a`",
+            "fnf()->u8{}",
+            "fng(){}"
+        ]
+    );
+}
+
+#[test]
+fn test_partial_output_with_holes() {
+    // `struct ;` would only report "expected identifier"
+    assert_eq!(
+        expand_partial(quote! { struct @[Foo @bad]; }),
+        ["error: cannot find `bad`"]
+    );
+    assert_eq!(
+        expand_partial(quote! { struct A; impl @bad for A {} }),
+        ["error: cannot find `bad`"]
+    );
+
+    // a failing item between good items leaves a `;`, which isn't an empty statement here
+    assert_eq!(
+        expand_partial(quote! { struct Good; @bad; struct AlsoGood; }),
+        ["error: cannot find `bad`"]
+    );
+    assert_eq!(
+        expand_partial(quote! { struct Good; const A: i32 = @bad; struct AlsoGood; }),
+        ["error: cannot find `bad`"]
+    );
+}
+
+#[test]
+fn test_partial_recovery() {
+    // a folded expression fails where it is evaluated, so the tokens after it are kept
+    assert_eq!(
+        expand_partial(quote! {
+            struct Good;
+            @let bad = 1 + x;
+            const A: i32 = @bad;
+            struct AlsoGood;
+        }),
+        ["error: cannot apply `+` to int `1` and ident `x`"]
+    );
+    assert_eq!(
+        expand_partial(quote! {
+            struct Good;
+            @let bad = 1 + x;
+            const A: i32 = 1;
+            struct AlsoGood;
+        }),
+        [
+            "error: cannot apply `+` to int `1` and ident `x`",
+            "structGood;",
+            "constA:i32=1;",
+            "structAlsoGood;"
+        ]
+    );
+
+    // a fragment that fails to parse is skipped up to its end
+    assert_eq!(
+        expand_partial(quote! {
+            struct Good;
+            @let = 1;
+            const A: i32 = 1;
+            @for x in { struct B; }
+            @(1 +) struct AlsoGood;
+        }),
+        [
+            "error: expected either identifer or list",
+            "error: blocks are currently unsupported",
+            "error: unexpected end of input, expected an expression",
+            "structGood;",
+            "constA:i32=1;",
+            "structAlsoGood;"
+        ]
+    );
+}
+
+#[test]
+fn test_partial_error_order() {
+    // parse and evaluation errors are reported in the order of the template,
+    // and the names of a `@let` that fails to parse aren't reported as missing
+    assert_eq!(
+        expand_partial(quote! {
+            @bad
+            @let y = ;
+            @y
+            @(1 +)
+            @for x in [@missing] {}
+            struct A;
+        }),
+        [
+            "error: cannot find `bad`",
+            "error: expected an expression",
+            "error: unexpected end of input, expected an expression",
+            "error: cannot find `missing`",
+            "structA;"
+        ]
+    );
+}
+
+/// Expands a template with partial output into its items,
+/// where a `compile_error!` is written as `error: <message>`.
+fn expand_partial(template: TokenStream) -> Vec<String> {
+    let output = syn::parse2::<syn::File>(macro_loop_partial(template)).unwrap();
+
+    output
+        .items
+        .into_iter()
+        .map(|item| match item {
+            syn::Item::Macro(item)
+                if item.mac.path.segments.last().unwrap().ident == "compile_error" =>
+            {
+                let message = syn::parse2::<syn::LitStr>(item.mac.tokens).unwrap();
+
+                format!("error: {}", message.value())
+            }
+
+            item => item.to_token_stream().to_string().replace(' ', ""),
+        })
+        .collect()
+}
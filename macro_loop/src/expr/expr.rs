@@ -1,14 +1,16 @@
-use std::mem::replace;
+use std::{mem::replace, rc::Rc};
 
 use derive_syn_parse::Parse;
-use proc_macro2::{Delimiter, Group, Span};
+use proc_macro2::{Delimiter, Group, Span, TokenStream};
+use quote::{ToTokens, TokenStreamExt};
 use syn::{
-    Error, Ident, Lit, LitBool, Token,
-    parse::{Parse, Parser},
+    Error, Ident, Lit, LitBool, LitInt, RangeLimits, Token,
+    ext::IdentExt,
+    parse::{Parse, Parser, discouraged::Speculative},
     parse2,
     punctuated::Punctuated,
     spanned::Spanned,
-    token::Bracket,
+    token::{Bracket, Paren},
 };
 
 use super::*;
@@ -19,6 +21,8 @@ pub enum Expr {
     Bin(Box<ExprBin>),
     Un(Box<ExprUn>),
     Method(ExprMethod),
+    Call(ExprCall),
+    Closure(Rc<ExprClosure>),
     List(ExprList),
     Paren(Box<Expr>),
 }
@@ -51,35 +55,67 @@ pub struct ExprMethod {
     pub inputs: Vec<Expr>,
 }
 
+pub struct ExprCall {
+    pub func: IdentStr,
+    pub inputs: Vec<Expr>,
+}
+
+/// `|<patterns>| <body>`, which is evaluated when a method calls it.
+pub struct ExprClosure {
+    pub inputs: Vec<Pattern>,
+    pub body: Expr,
+    tokens: TokenStream,
+}
+
+impl ToTokens for ExprClosure {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.tokens.clone());
+    }
+}
+
 impl Parse for Expr {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let mut output = Expr::parse_single(input)?;
-
-        while let Some(op) = BinOp::option_parse(input) {
-            let rhs = Expr::parse_single(input)?;
-
-            if let Expr::Bin(ref mut bin) = output {
-                let ExprBin {
-                    lhs: _,
-                    op: output_op,
-                    rhs: ref mut output_rhs,
-                } = **bin;
-
-                if output_op.lvl() > op.lvl() {
-                    output_rhs.try_replace(|output_rhs| Self::bin(output_rhs, op, rhs))?;
-                } else {
-                    output.try_replace(|output| Self::bin(output, op, rhs))?;
-                }
-            } else {
-                output.try_replace(|output| Self::bin(output, op, rhs))?;
+        Self::parse_bin(input, BinOpLvl::Range)
+    }
+}
+
+impl Expr {
+    /// Precedence climbing: parses a chain of binary operators that bind no looser than `max_lvl`.
+    fn parse_bin(input: syn::parse::ParseStream, max_lvl: BinOpLvl) -> syn::Result<Self> {
+        let mut output = Self::parse_single(input)?;
+        let mut prev_lvl = None;
+
+        loop {
+            let fork = input.fork();
+            let op = match BinOp::option_parse(&fork) {
+                Some(op) if op.lvl() <= max_lvl => op,
+                _ => break,
+            };
+            input.advance_to(&fork);
+
+            let lvl = op.lvl();
+            if !lvl.is_associative() && prev_lvl == Some(lvl) {
+                return Err(Error::new(
+                    op.span(),
+                    match lvl {
+                        BinOpLvl::Range => "range operators cannot be chained",
+                        _ => "comparison operators cannot be chained",
+                    },
+                ));
             }
+
+            let rhs = match lvl.tighter() {
+                Some(rhs_lvl) => Self::parse_bin(input, rhs_lvl)?,
+                None => Self::parse_single(input)?,
+            };
+
+            output = Self::bin(output, op, rhs);
+            prev_lvl = Some(lvl);
         }
 
         Ok(output)
     }
-}
 
-impl Expr {
     fn parse_single(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut output = Self::parse_base(input)?;
 
@@ -88,16 +124,7 @@ impl Expr {
                 input.parse::<Token![.]>().unwrap();
 
                 let method = input.parse::<IdentStr>()?;
-
-                let inputs = input.parse::<Group>()?;
-                if inputs.delimiter() != Delimiter::Parenthesis {
-                    return Err(Error::new(inputs.span(), "expected `()`"));
-                }
-
-                let inputs = Punctuated::<Expr, Token![,]>::parse_terminated
-                    .parse2(inputs.stream())?
-                    .into_iter()
-                    .collect();
+                let inputs = Self::parse_inputs(input)?;
 
                 output.replace(|output| {
                     Self::Method(ExprMethod {
@@ -113,15 +140,18 @@ impl Expr {
             if input.peek(Bracket) {
                 let group = input.parse::<Group>()?;
 
-                let idx = Expr::parse.parse2(group.stream())?;
+                let (method, inputs) = Self::parse_index.parse2(group.stream())?;
+                let method = IdentStr::new(method, group.span())?;
 
                 output.replace(|output| {
                     Self::Method(ExprMethod {
                         base: Box::new(output),
-                        method: IdentStr::new("index", group.span()),
-                        inputs: vec![idx],
+                        method,
+                        inputs,
                     })
                 });
+
+                continue;
             }
 
             break;
@@ -130,18 +160,67 @@ impl Expr {
         Ok(output)
     }
 
+    /// Parses the inside of `[]`, which is either an index or a slice range (`a..b`, `a..`, `..b`, `..`).
+    fn parse_index(input: syn::parse::ParseStream) -> syn::Result<(&'static str, Vec<Self>)> {
+        let start = if input.peek(Token![..]) {
+            None
+        } else {
+            let idx = Self::parse_bin(input, BinOpLvl::LogicalOr)?;
+            if input.is_empty() {
+                return Ok(("index", vec![idx]));
+            }
+
+            Some(idx)
+        };
+
+        let limits = input.parse::<RangeLimits>()?;
+
+        let end = if input.is_empty() {
+            None
+        } else {
+            Some(Self::parse_bin(input, BinOpLvl::LogicalOr)?)
+        };
+
+        let start =
+            start.unwrap_or_else(|| Self::Value(Value::Int(LitInt::new("0", limits.span()))));
+
+        Ok(match (limits, end) {
+            (RangeLimits::HalfOpen(_), None) => ("slice", vec![start]),
+            (RangeLimits::HalfOpen(_), Some(end)) => ("slice", vec![start, end]),
+            (RangeLimits::Closed(_), Some(end)) => ("slice_inclusive", vec![start, end]),
+
+            (RangeLimits::Closed(limits), None) => {
+                return Err(Error::new(
+                    limits.span(),
+                    "inclusive ranges must have an end",
+                ));
+            }
+        })
+    }
+
     fn parse_base(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![|]) || input.peek(Token![||]) {
+            return Ok(Self::Closure(Rc::new(Self::parse_closure(input)?)));
+        }
+
         if let Some(op) = UnOp::option_parse(input) {
             let base = Expr::parse_single(input)?;
 
-            return Self::un(op, base);
+            return Ok(Self::un(op, base));
         };
 
         if let Some(lit) = input.parse::<Option<Lit>>()? {
             return Ok(Self::Value(Value::from_lit(lit)?));
         };
 
-        if input.peek(Ident) {
+        if input.peek(Ident) && input.peek2(Paren) {
+            return Ok(Self::Call(ExprCall {
+                func: input.parse()?,
+                inputs: Self::parse_inputs(input)?,
+            }));
+        };
+
+        if input.peek(Ident::peek_any) {
             return Ok(Self::Value(Value::Ident(input.parse().unwrap())));
         };
 
@@ -184,32 +263,77 @@ impl Expr {
         Err(input.error("expected an expression"))
     }
 
-    fn bin(self, op: BinOp, rhs: Self) -> syn::Result<Self> {
-        Ok(
-            if let (Self::Value(self_), Self::Value(rhs)) = (&self, &rhs) {
-                Self::Value(self_.bin_op(op, rhs)?)
-            } else {
-                Self::Bin(Box::new(ExprBin { lhs: self, op, rhs }))
-            },
-        )
-    }
+    fn parse_closure(input: syn::parse::ParseStream) -> syn::Result<ExprClosure> {
+        let start = input.cursor();
 
-    fn un(op: UnOp, base: Self) -> syn::Result<Self> {
-        Ok(if let Self::Value(base) = base {
-            Self::Value(base.un_op(op)?)
-        } else {
-            Self::Un(Box::new(ExprUn { op, base }))
+        let mut inputs = Vec::new();
+        if input.parse::<Option<Token![||]>>()?.is_none() {
+            input.parse::<Token![|]>()?;
+
+            while !input.peek(Token![|]) {
+                inputs.push(input.parse()?);
+
+                if input.parse::<Option<Token![,]>>()?.is_none() {
+                    break;
+                }
+            }
+
+            input.parse::<Token![|]>()?;
+        }
+
+        let body = input.parse()?;
+
+        let mut tokens = TokenStream::new();
+        let mut cursor = start;
+        while cursor != input.cursor() {
+            let (token, next) = cursor.token_tree().unwrap();
+            tokens.append(token);
+            cursor = next;
+        }
+
+        Ok(ExprClosure {
+            inputs,
+            body,
+            tokens,
         })
     }
 
-    fn replace(&mut self, value: impl FnOnce(Self) -> Self) {
-        *self = value(replace(self, Self::temporary()));
+    fn parse_inputs(input: syn::parse::ParseStream) -> syn::Result<Vec<Self>> {
+        let inputs = input.parse::<Group>()?;
+        if inputs.delimiter() != Delimiter::Parenthesis {
+            return Err(Error::new(inputs.span(), "expected `()`"));
+        }
+
+        Ok(Punctuated::<Expr, Token![,]>::parse_terminated
+            .parse2(inputs.stream())?
+            .into_iter()
+            .collect())
     }
 
-    fn try_replace(&mut self, value: impl FnOnce(Self) -> syn::Result<Self>) -> syn::Result<()> {
-        *self = value(replace(self, Self::temporary()))?;
+    /// Folds operations on literals while parsing.
+    /// operations that fail are kept, so their errors are reported where they are evaluated.
+    fn bin(self, op: BinOp, rhs: Self) -> Self {
+        if let (Self::Value(self_), Self::Value(rhs)) = (&self, &rhs)
+            && let Ok(value) = self_.bin_op(op, rhs)
+        {
+            return Self::Value(value);
+        }
 
-        Ok(())
+        Self::Bin(Box::new(ExprBin { lhs: self, op, rhs }))
+    }
+
+    fn un(op: UnOp, base: Self) -> Self {
+        if let Self::Value(value) = &base
+            && let Ok(value) = value.un_op(op)
+        {
+            return Self::Value(value);
+        }
+
+        Self::Un(Box::new(ExprUn { op, base }))
+    }
+
+    fn replace(&mut self, value: impl FnOnce(Self) -> Self) {
+        *self = value(replace(self, Self::temporary()));
     }
 
     fn temporary() -> Self {
@@ -1,4 +1,4 @@
-use super::{fragment::*, value::*, *};
+use super::{fragment::*, name::*, value::*, *};
 
 mod expr;
 mod ops;
@@ -26,6 +26,8 @@ pub enum BinOp {
     LogicalAnd(Token![&&]),
     LogicalOr(Token![||]),
 
+    In(Token![in]),
+
     Range(Token![..]),
     RangeInclusive(Token![..=]),
 }
@@ -37,18 +39,20 @@ pub enum UnOp {
     Not(Token![!]),
 }
 
+/// Binary operator precedence, from the tightest binding level to the loosest.
+/// Matches Rust's binary operator precedence.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BinOpLvl {
-    ShlShr,
     MulDivRem,
     AddSub,
+    ShlShr,
     BitAnd,
     BitXor,
     BitOr,
-    Range,
-    Eq,
+    Cmp,
     LogicalAnd,
     LogicalOr,
+    Range,
 }
 
 impl BinOp {
@@ -91,6 +95,8 @@ impl BinOp {
         option_parse!(< => Lt);
         option_parse!(> => Gt);
 
+        option_parse!(in => In);
+
         None
     }
 
@@ -103,15 +109,42 @@ impl BinOp {
             Self::BitOr(_) => BinOpLvl::BitOr,
             Self::Shl(_) | Self::Shr(_) => BinOpLvl::ShlShr,
             Self::Range(_) | Self::RangeInclusive(_) => BinOpLvl::Range,
-            Self::Eq(_) | Self::Ne(_) | Self::Lt(_) | Self::Gt(_) | Self::Le(_) | Self::Ge(_) => {
-                BinOpLvl::Eq
-            }
+            Self::Eq(_)
+            | Self::Ne(_)
+            | Self::Lt(_)
+            | Self::Gt(_)
+            | Self::Le(_)
+            | Self::Ge(_)
+            | Self::In(_) => BinOpLvl::Cmp,
             Self::LogicalAnd(_) => BinOpLvl::LogicalAnd,
             Self::LogicalOr(_) => BinOpLvl::LogicalOr,
         }
     }
 }
 
+impl BinOpLvl {
+    /// The level that binds one step tighter than `self`.
+    pub fn tighter(self) -> Option<Self> {
+        Some(match self {
+            Self::MulDivRem => return None,
+            Self::AddSub => Self::MulDivRem,
+            Self::ShlShr => Self::AddSub,
+            Self::BitAnd => Self::ShlShr,
+            Self::BitXor => Self::BitAnd,
+            Self::BitOr => Self::BitXor,
+            Self::Cmp => Self::BitOr,
+            Self::LogicalAnd => Self::Cmp,
+            Self::LogicalOr => Self::LogicalAnd,
+            Self::Range => Self::LogicalOr,
+        })
+    }
+
+    /// Comparisons and ranges are non-associative, like in Rust (`a < b < c` is an error).
+    pub fn is_associative(self) -> bool {
+        !matches!(self, Self::Cmp | Self::Range)
+    }
+}
+
 impl UnOp {
     pub fn option_parse(input: syn::parse::ParseStream) -> Option<Self> {
         macro_rules! option_parse {
@@ -134,6 +167,16 @@ impl UnOp {
     }
 }
 
+impl Spanned for UnOp {
+    fn span(&self) -> proc_macro2::Span {
+        match self {
+            Self::Neg(self_) => self_.span,
+
+            Self::Not(self_) => self_.span,
+        }
+    }
+}
+
 impl Spanned for BinOp {
     fn span(&self) -> proc_macro2::Span {
         match self {
@@ -158,6 +201,8 @@ impl Spanned for BinOp {
             Self::LogicalAnd(self_) => syn::spanned::Spanned::span(self_),
             Self::LogicalOr(self_) => syn::spanned::Spanned::span(self_),
 
+            Self::In(self_) => self_.span,
+
             Self::Range(self_) => syn::spanned::Spanned::span(self_),
             Self::RangeInclusive(self_) => syn::spanned::Spanned::span(self_),
         }
@@ -2,7 +2,7 @@ use derive_syn_parse::Parse;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use syn::{
-    Ident, Token,
+    Ident, LitStr, Token,
     token::{Bracket, Paren},
 };
 
@@ -30,9 +30,21 @@ pub enum Frag {
     #[peek(Bracket, name = "`[]`")]
     Ident(FragConcat),
 
+    #[peek_with(peek_dbg, name = "dbg")]
+    Dbg(FragDbg),
+
+    #[peek_with(peek_trace, name = "trace")]
+    Trace(FragTrace),
+
     #[peek(Ident, name = "a name")]
     Name(FragName),
 
+    #[peek(LitStr, name = "a string")]
+    Str(FragStr),
+
+    #[peek(Token![#], name = "a doc comment")]
+    Doc(FragDoc),
+
     #[peek(Token![@], name = "`@`")]
     Cancel(Token![@]),
 }
@@ -57,9 +69,17 @@ impl ApplyFragment for Frag {
             Self::Let(self_) => self_.apply(namespace, tokens),
             Self::Expr(self_) => self_.apply(namespace, tokens),
             Self::Ident(self_) => self_.apply(namespace, tokens),
+            Self::Dbg(self_) => self_.apply(namespace, tokens),
+            Self::Trace(self_) => self_.apply(namespace, tokens),
             Self::Name(self_) => self_.apply(namespace, tokens),
+            Self::Str(self_) => self_.apply(namespace, tokens),
+            Self::Doc(self_) => self_.apply(namespace, tokens),
+
+            Self::Cancel(self_) => {
+                self_.to_tokens(tokens);
 
-            Self::Cancel(self_) => Ok(self_.to_tokens(tokens)),
+                Ok(())
+            }
         }
     }
 }
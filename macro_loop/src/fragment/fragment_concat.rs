@@ -1,8 +1,10 @@
 use derive_syn_parse::Parse;
-use proc_macro2::{Span, TokenStream};
+use std::ffi::CString;
+
+use proc_macro2::{Literal, Span, TokenStream};
 use quote::ToTokens;
 use syn::{
-    Error, Ident, LitStr, Token,
+    Error, Ident, Lifetime, Lit, LitByteStr, LitCStr, LitChar, LitStr, Token,
     ext::IdentExt,
     parse::{Parse, ParseStream, Parser},
     token::Bracket,
@@ -15,6 +17,9 @@ pub struct FragConcat {
     #[bracket]
     _brackets: Bracket,
     #[inside(_brackets)]
+    #[call(parse_case)]
+    case: Option<Case>,
+    #[inside(_brackets)]
     #[call(parse_segments)]
     segments: Vec<Segment>,
     #[inside(_brackets)]
@@ -26,10 +31,16 @@ pub struct FragConcat {
 
 #[derive(Parse)]
 enum Segment {
-    #[peek_with(|input: ParseStream| input.peek(Ident::peek_any), name = "an ident")]
+    #[peek_with(peek_ident, name = "an ident")]
     Ident(#[call(Ident::parse_any)] Ident),
 
-    #[peek(Token![@], name = "an ident")]
+    #[peek(Lit, name = "a literal")]
+    Lit(Lit),
+
+    #[peek(Token![::], name = "`::`")]
+    PathSep(Token![::]),
+
+    #[peek(Token![@], name = "`@`")]
     Fragment(SegmentFragment),
 }
 
@@ -45,17 +56,131 @@ impl ApplyFragment for FragConcat {
         namespace: &mut Namespace<'v, 'v>,
         tokens: &mut TokenStream,
     ) -> syn::Result<()> {
-        let str = self
-            .segments
+        let type_ = self.type_.as_ref().map(|type_| type_.str());
+
+        if self.case == Some(Case::Kebab) && matches!(type_, None | Some("lifetime" | "path")) {
+            return Err(Error::new(
+                self._brackets.span.join(),
+                "`kebab:` can't make an identifier. use it with a kind like `=> str`",
+            ));
+        }
+
+        // `::` separates path segments, which are converted to a case separately
+        let mut parts = vec![String::new()];
+        for seg in &self.segments {
+            match seg {
+                Segment::PathSep(path_sep) if type_ != Some("path") => {
+                    return Err(Error::new_spanned(
+                        path_sep,
+                        "`::` is only allowed in `=> path` concats",
+                    ));
+                }
+                Segment::PathSep(_) => parts.push(String::new()),
+
+                _ => *parts.last_mut().unwrap() += &seg.try_to_string(namespace)?,
+            }
+        }
+
+        let str = parts
             .iter()
-            .map(|seg| seg.try_to_string(namespace))
-            .collect::<syn::Result<String>>()?;
+            .map(|part| match self.case {
+                Some(case) if !part.is_empty() => case.convert(part),
+                _ => part.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("::");
 
         let span = self.segments.iter().map(|seg| seg.span()).nth(0).unwrap();
 
-        let value = match self.type_.as_ref().map(|type_| type_.str()) {
+        let value = match type_ {
+            None => Value::Ident(IdentStr::new(str, span)?),
             Some("str") => Value::Str(LitStr::new(&str, span)),
-            None => Value::Ident(IdentStr::new(str.into_boxed_str(), span)),
+            Some("raw_str") => Value::Str(raw_str_lit(&str, span)?),
+            Some("bytes") => Value::ByteStr(LitByteStr::new(str.as_bytes(), span)),
+
+            Some("cstr") => match CString::new(str) {
+                Ok(str) => Value::CStr(LitCStr::new(&str, span)),
+                Err(_) => return Err(Error::new(span, "c strings cannot contain nul bytes")),
+            },
+
+            Some("char") => {
+                let mut chars = str.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Value::Char(LitChar::new(c, span)),
+                    _ => {
+                        return Err(Error::new(span, format!("`{str}` is not a single char")));
+                    }
+                }
+            }
+
+            Some("int") => match syn::parse_str(&str) {
+                Ok(Lit::Int(mut lit)) if INT_SUFFIXES.contains(&lit.suffix()) => {
+                    lit.set_span(span);
+                    Value::Int(lit)
+                }
+                _ => {
+                    return Err(Error::new(
+                        span,
+                        format!("`{str}` is not a valid int literal"),
+                    ));
+                }
+            },
+
+            Some("float") => match syn::parse_str(&str) {
+                Ok(Lit::Float(mut lit)) if ["", "f32", "f64"].contains(&lit.suffix()) => {
+                    lit.set_span(span);
+                    Value::Float(lit)
+                }
+                _ => {
+                    return Err(Error::new(
+                        span,
+                        format!("`{str}` is not a valid float literal"),
+                    ));
+                }
+            },
+
+            Some("lifetime") => {
+                let name = str.strip_prefix('\'').unwrap_or(&str);
+                if !is_valid_ident(name) {
+                    return Err(Error::new(
+                        span,
+                        format!("`'{name}` is not a valid lifetime"),
+                    ));
+                }
+
+                Lifetime::new(&format!("'{name}"), span).to_tokens(tokens);
+
+                return Ok(());
+            }
+
+            Some("path") => {
+                let segments = str.split("::").collect::<Vec<_>>();
+
+                for (idx, segment) in segments.iter().enumerate() {
+                    // a leading `::` makes the first segment empty
+                    if idx == 0 && segment.is_empty() && segments.len() > 1 {
+                        continue;
+                    }
+
+                    if !is_valid_ident(segment) {
+                        return Err(Error::new(span, format!("`{str}` is not a valid path")));
+                    }
+                }
+
+                for (idx, segment) in segments.iter().enumerate() {
+                    if idx > 0 {
+                        <Token![::]>::default().to_tokens(tokens);
+                    }
+                    // `self`, `super`, `crate` and `Self` start paths, and can't be raw
+                    if is_path_keyword(segment) {
+                        Ident::new(segment, span).to_tokens(tokens);
+                    } else if !segment.is_empty() {
+                        IdentStr::new(*segment, span)?.to_tokens(tokens);
+                    }
+                }
+
+                return Ok(());
+            }
 
             _ => return Err(Error::new_spanned(&self.type_, "invalid concat type")),
         };
@@ -69,7 +194,9 @@ impl ApplyFragment for FragConcat {
 impl Segment {
     fn try_to_string(&self, namespace: &Namespace) -> syn::Result<String> {
         Ok(match self {
-            Segment::Ident(ident) => ident.to_string(),
+            Segment::Ident(ident) => ident.unraw().to_string(),
+            Segment::Lit(lit) => Value::from_lit(lit.clone())?.try_to_string()?,
+            Segment::PathSep(_) => "::".to_string(),
             Segment::Fragment(frag) => {
                 let mut namespace = namespace.fork();
 
@@ -88,11 +215,62 @@ impl Segment {
     fn span(&self) -> Span {
         match self {
             Self::Ident(self_) => self_.span(),
+            Self::Lit(self_) => self_.span(),
+            Self::PathSep(self_) => self_.spans[0],
             Segment::Fragment(self_) => self_._at_token.span,
         }
     }
 }
 
+const INT_SUFFIXES: &[&str] = &[
+    "", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// A raw string literal, with enough `#`s around it for the quotes inside it.
+///
+/// Raw strings can't contain every char (such as a lone `\r`), so this can fail.
+fn raw_str_lit(str: &str, span: Span) -> syn::Result<LitStr> {
+    // a run of `str.len()` `#`s can't follow a quote inside `str`
+    let count = (0..str.len())
+        .find(|&count| !str.contains(&format!("\"{}", "#".repeat(count))))
+        .unwrap_or(str.len());
+    let hashes = "#".repeat(count);
+
+    match format!("r{hashes}\"{str}\"{hashes}").parse::<Literal>() {
+        Ok(mut lit) => {
+            lit.set_span(span);
+
+            match Lit::new(lit) {
+                Lit::Str(lit) => Ok(lit),
+                _ => Err(Error::new(span, format!("{str:?} cannot be a raw string"))),
+            }
+        }
+        Err(_) => Err(Error::new(span, format!("{str:?} cannot be a raw string"))),
+    }
+}
+
+fn peek_ident(input: ParseStream) -> bool {
+    input.peek(Ident::peek_any)
+}
+
+/// Parses a case modifier like `snake:`.
+fn parse_case(input: ParseStream) -> syn::Result<Option<Case>> {
+    if !(input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::])) {
+        return Ok(None);
+    }
+
+    let name = input.parse::<Ident>()?;
+    input.parse::<Token![:]>()?;
+
+    match Case::from_name(&name.to_string()) {
+        Some(case) => Ok(Some(case)),
+        None => Err(Error::new(
+            name.span(),
+            "unknown case. expected `snake`, `camel`, `pascal`, `screaming_snake` or `kebab`",
+        )),
+    }
+}
+
 fn parse_segments<T: Parse>(input: ParseStream) -> syn::Result<Vec<T>> {
     let mut items = Vec::new();
 
@@ -0,0 +1,59 @@
+use proc_macro2::{Delimiter, Group, Ident, TokenStream};
+use syn::{
+    Error,
+    parse::{Parse, ParseStream, Parser},
+};
+
+use super::*;
+
+/// `@dbg(expr)`, which reports the value of `expr` as a warning and emits nothing.
+pub struct FragDbg {
+    group: Group,
+    expr: Expr,
+}
+
+impl Parse for FragDbg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Ident>()?;
+
+        let group = input.parse::<Group>()?;
+        if group.delimiter() != Delimiter::Parenthesis {
+            return Err(Error::new(group.span(), "expected `()`"));
+        }
+
+        let expr = Expr::parse.parse2(group.stream())?;
+
+        Ok(Self { group, expr })
+    }
+}
+
+impl ApplyFragment for FragDbg {
+    fn apply<'s: 'v, 'v>(
+        &'s self,
+        namespace: &mut Namespace<'v, 'v>,
+        _tokens: &mut TokenStream,
+    ) -> syn::Result<()> {
+        let value = Value::from_expr(&self.expr, namespace)?;
+
+        report(
+            self.group.span(),
+            format!(
+                "`{}` = {} `{}`",
+                self.group.stream(),
+                value.kind_name(),
+                value.render()
+            ),
+        );
+
+        Ok(())
+    }
+}
+
+pub fn peek_dbg(input: ParseStream) -> bool {
+    input.peek(syn::Ident)
+        && input.peek2(syn::token::Paren)
+        && input
+            .fork()
+            .parse::<Ident>()
+            .is_ok_and(|ident| ident == "dbg")
+}
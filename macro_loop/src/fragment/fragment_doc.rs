@@ -0,0 +1,49 @@
+use derive_syn_parse::Parse;
+use proc_macro2::{Ident, TokenStream};
+use quote::ToTokens;
+use syn::{Error, LitStr, Token, token::Bracket};
+
+use super::*;
+
+/// A doc comment (`///`, `//!` or `#[doc = "..."]`) with `{@expr}` interpolations.
+#[derive(Parse)]
+pub struct FragDoc {
+    pound_token: Token![#],
+    bang_token: Option<Token![!]>,
+    #[bracket]
+    brackets: Bracket,
+    #[inside(brackets)]
+    #[call(parse_doc)]
+    str: Interpolation,
+}
+
+impl ApplyFragment for FragDoc {
+    fn apply<'s: 'v, 'v>(
+        &'s self,
+        namespace: &mut Namespace<'v, 'v>,
+        tokens: &mut TokenStream,
+    ) -> syn::Result<()> {
+        let str = self.str.resolve(namespace)?;
+
+        self.pound_token.to_tokens(tokens);
+        self.bang_token.to_tokens(tokens);
+        self.brackets.surround(tokens, |tokens| {
+            Ident::new("doc", self.brackets.span.join()).to_tokens(tokens);
+            <Token![=]>::default().to_tokens(tokens);
+            str.to_tokens(tokens);
+        });
+
+        Ok(())
+    }
+}
+
+fn parse_doc(input: syn::parse::ParseStream) -> syn::Result<Interpolation> {
+    let ident = input.parse::<Ident>()?;
+    if ident != "doc" {
+        return Err(Error::new(ident.span(), "expected a doc comment"));
+    }
+
+    input.parse::<Token![=]>()?;
+
+    Interpolation::new(&input.parse::<LitStr>()?)
+}
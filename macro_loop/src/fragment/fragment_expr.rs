@@ -19,7 +19,7 @@ impl ApplyFragment for FragExpr {
         namespace: &mut Namespace<'v, 'v>,
         tokens: &mut TokenStream,
     ) -> syn::Result<()> {
-        let value = Value::from_expr(&self.expr, &namespace)?;
+        let value = Value::from_expr(&self.expr, namespace)?;
 
         value.to_tokens(tokens);
 
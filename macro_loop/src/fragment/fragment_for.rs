@@ -1,3 +1,5 @@
+use std::fmt::{self, Display, Formatter};
+
 use derive_syn_parse::Parse;
 use proc_macro2::TokenStream;
 use quote::TokenStreamExt;
@@ -29,7 +31,27 @@ impl ApplyFragment for FragFor {
         namespace: &mut Namespace<'v, 'v>,
         tokens: &mut TokenStream,
     ) -> syn::Result<()> {
-        self.apply_inner(namespace, tokens, 0)
+        let mut bindings = Vec::new();
+
+        self.apply_inner(namespace, tokens, 0, &mut bindings)
+            .map_err(|err| match bindings.is_empty() {
+                true => err,
+                false => with_note(
+                    err,
+                    format!("while expanding `{self}` with {}", bindings.join(", ")),
+                ),
+            })
+    }
+}
+
+impl Display for FragFor {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| format!("{} in ...", segment.pat));
+
+        write!(f, "@for {}", segments.collect::<Vec<_>>().join(", "))
     }
 }
 
@@ -39,6 +61,7 @@ impl FragFor {
         namespace: &mut Namespace,
         tokens: &mut TokenStream,
         seg_idx: usize,
+        bindings: &mut Vec<String>,
     ) -> syn::Result<()> {
         if self.segments.len() <= seg_idx {
             tokens.append_all(self.body.resolve(namespace)?);
@@ -48,19 +71,24 @@ impl FragFor {
 
         let segment = &self.segments[seg_idx];
 
-        let values = Value::from_expr(&segment.items, &namespace)?;
+        let values = Value::from_expr(&segment.items, namespace)?;
 
         let values = if let Value::List(values) = &*values {
             &values.items
         } else {
-            return Err(Error::new_spanned(&values, "expected a list"));
+            return Err(Error::new_spanned(&values, values.expected("a list")));
         };
 
+        // the bindings of a failing iteration stay in `bindings` for the error note
         for value in values {
+            bindings.push(format!("{} = {}", segment.pat, value.preview()));
+
             let mut namespace = namespace.fork();
             namespace.insert_pat(&segment.pat, ValueRef::Ref(value))?;
 
-            self.apply_inner(&mut namespace, tokens, seg_idx + 1)?;
+            self.apply_inner(&mut namespace, tokens, seg_idx + 1, bindings)?;
+
+            bindings.pop();
         }
 
         Ok(())
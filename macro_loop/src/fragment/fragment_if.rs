@@ -21,15 +21,25 @@ impl ApplyFragment for FragIf {
         namespace: &mut Namespace<'v, 'v>,
         tokens: &mut TokenStream,
     ) -> syn::Result<()> {
-        let condition = Value::from_expr(&self.condition, &namespace)?;
+        let condition = Value::from_expr(&self.condition, namespace)?;
 
         let condition = match &*condition {
             Value::Bool(condition) => condition.value,
-            _ => return Err(Error::new_spanned(&self._if_token, "expected a bool")),
+            _ => {
+                return Err(Error::new_spanned(
+                    self._if_token,
+                    condition.expected("a bool condition"),
+                ));
+            }
         };
 
         if condition {
-            tokens.append_all(self.body.resolve(namespace)?);
+            let body = self
+                .body
+                .resolve(namespace)
+                .map_err(|err| with_note(err, "while expanding `@if ...`"))?;
+
+            tokens.append_all(body);
         }
 
         Ok(())
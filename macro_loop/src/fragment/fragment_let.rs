@@ -19,10 +19,16 @@ impl ApplyFragment for FragLet {
         namespace: &mut Namespace<'v, 'v>,
         _tokens: &mut TokenStream,
     ) -> syn::Result<()> {
-        let value = Value::from_expr(&self.value, &namespace)?;
+        let result = Value::from_expr(&self.value, namespace)
+            .and_then(|value| namespace.insert_pat(&self.pat, value));
 
-        namespace.insert_pat(&self.pat, value)?;
+        // later uses of the names would only repeat the error
+        if result.is_err() {
+            for name in self.pat.names() {
+                namespace.poison(name);
+            }
+        }
 
-        Ok(())
+        result
     }
 }
@@ -8,6 +8,12 @@ pub struct FragName {
     name: Name,
 }
 
+impl FragName {
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+}
+
 impl ApplyFragment for FragName {
     fn apply<'s: 'v, 'v>(
         &'s self,
@@ -0,0 +1,224 @@
+use std::{iter::Peekable, mem::take, str::CharIndices};
+
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    Error, LitStr,
+    parse::{Parse, ParseStream, Parser},
+};
+
+use super::*;
+
+pub struct FragStr {
+    str: Interpolation,
+}
+
+/// The contents of a string literal with `{@expr}` interpolations,
+/// which are formatted like `format`'s placeholders (`{@N:02}`).
+pub struct Interpolation {
+    span: Span,
+    parts: Vec<InterpolationPart>,
+}
+
+enum InterpolationPart {
+    Text(String),
+    Expr { expr: Expr, spec: String },
+}
+
+impl Parse for FragStr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            str: Interpolation::new(&input.parse()?)?,
+        })
+    }
+}
+
+impl ApplyFragment for FragStr {
+    fn apply<'s: 'v, 'v>(
+        &'s self,
+        namespace: &mut Namespace<'v, 'v>,
+        tokens: &mut TokenStream,
+    ) -> syn::Result<()> {
+        self.str.resolve(namespace)?.to_tokens(tokens);
+
+        Ok(())
+    }
+}
+
+impl Interpolation {
+    pub fn new(lit: &LitStr) -> syn::Result<Self> {
+        let span = lit.span();
+        let str = lit.value();
+
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        let mut chars = str.char_indices().peekable();
+        while let Some((idx, c)) = chars.next() {
+            let next = chars.peek().map(|(_, next)| *next);
+
+            match c {
+                '{' | '}' if next == Some(c) => {
+                    chars.next();
+                    text.push(c);
+                }
+
+                '{' if next == Some('@') => {
+                    let Some(end) = interpolation_end(&mut chars) else {
+                        return Err(Error::new(span, "unterminated `{@`. use `{{` to emit `{`"));
+                    };
+
+                    let (expr, spec) = split_spec(&str[idx + 1..end]);
+
+                    let expr = expr
+                        .parse::<TokenStream>()
+                        .map_err(|err| Error::new(span, err))?;
+
+                    parts.push(InterpolationPart::Text(take(&mut text)));
+                    parts.push(InterpolationPart::Expr {
+                        expr: Expr::parse.parse2(respan(expr, span))?,
+                        spec: spec.to_string(),
+                    });
+                }
+
+                // like `format!`, braces that don't delimit an interpolation must be escaped
+                '{' => {
+                    return Err(Error::new(
+                        span,
+                        "expected `@` after `{` in interpolated string. use `{{` to emit `{`",
+                    ));
+                }
+                '}' => {
+                    return Err(Error::new(
+                        span,
+                        "unmatched `}` in interpolated string. use `}}` to emit `}`",
+                    ));
+                }
+
+                c => text.push(c),
+            }
+        }
+
+        parts.push(InterpolationPart::Text(text));
+
+        Ok(Self { span, parts })
+    }
+
+    pub fn resolve(&self, namespace: &Namespace) -> syn::Result<LitStr> {
+        let mut output = String::new();
+
+        for part in &self.parts {
+            match part {
+                InterpolationPart::Text(text) => output += text,
+
+                InterpolationPart::Expr { expr, spec } => {
+                    let value = Value::from_expr(expr, namespace)?;
+
+                    output += &format(&format!("{{:{spec}}}"), &[value], namespace, self.span)?;
+                }
+            }
+        }
+
+        Ok(LitStr::new(&output, self.span))
+    }
+}
+
+/// Finds the `}` that closes an interpolation,
+/// skipping the braces of nested groups and of string and char literals.
+fn interpolation_end(chars: &mut Peekable<CharIndices>) -> Option<usize> {
+    let mut depth = 1;
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '"' | '\'' => skip_literal(c, chars)?,
+            _ => {}
+        }
+
+        if depth == 0 {
+            return Some(idx);
+        }
+    }
+
+    None
+}
+
+/// Splits `@expr:spec` at the first `:` that isn't part of a `::`, a literal or a group.
+fn split_spec(str: &str) -> (&str, &str) {
+    let mut chars = str.char_indices().peekable();
+
+    let mut depth = 0;
+    let mut prev = None;
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+
+            '"' | '\'' => {
+                let Some(()) = skip_literal(c, &mut chars) else {
+                    break;
+                };
+            }
+
+            ':' if depth == 0
+                && prev != Some(':')
+                && chars.peek().map(|(_, c)| *c) != Some(':') =>
+            {
+                return (&str[..idx], &str[idx + 1..]);
+            }
+
+            _ => {}
+        }
+
+        prev = Some(c);
+    }
+
+    (str, "")
+}
+
+/// Skips the rest of a string or char literal after its opening quote,
+/// returning `None` if it is unterminated.
+fn skip_literal(quote: char, chars: &mut Peekable<CharIndices>) -> Option<()> {
+    if quote == '"' {
+        let mut escaped = false;
+        chars.find(|&(_, c)| {
+            let is_end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+
+            is_end
+        })?;
+
+        return Some(());
+    }
+
+    // a char literal, unlike a lifetime, is closed by another `'`
+    let mut ahead = chars.clone().map(|(_, c)| c);
+    match (ahead.next(), ahead.next()) {
+        (Some('\\'), _) => {
+            chars.nth(1);
+            chars.find(|&(_, c)| c == '\'')?;
+        }
+        (Some(_), Some('\'')) => _ = chars.nth(1),
+        _ => {}
+    }
+
+    Some(())
+}
+
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut new_group = Group::new(group.delimiter(), respan(group.stream(), span));
+                new_group.set_span(span);
+
+                token = TokenTree::Group(new_group);
+            } else {
+                token.set_span(span);
+            }
+
+            token
+        })
+        .collect()
+}
@@ -0,0 +1,37 @@
+use derive_syn_parse::Parse;
+use proc_macro2::{Ident, TokenStream};
+use quote::TokenStreamExt;
+use syn::{parse::ParseStream, token::Brace};
+
+use super::*;
+
+/// `@trace { ... }`, which reports every fragment evaluated inside it as a warning.
+#[derive(Parse)]
+pub struct FragTrace {
+    _trace_ident: Ident,
+    #[brace]
+    _braces: Brace,
+    #[inside(_braces)]
+    body: NameStream,
+}
+
+impl ApplyFragment for FragTrace {
+    fn apply<'s: 'v, 'v>(
+        &'s self,
+        namespace: &mut Namespace<'v, 'v>,
+        tokens: &mut TokenStream,
+    ) -> syn::Result<()> {
+        tokens.append_all(trace(|| self.body.resolve(namespace))?);
+
+        Ok(())
+    }
+}
+
+pub fn peek_trace(input: ParseStream) -> bool {
+    input.peek(syn::Ident)
+        && input.peek2(Brace)
+        && input
+            .fork()
+            .parse::<Ident>()
+            .is_ok_and(|ident| ident == "trace")
+}
@@ -2,15 +2,23 @@ use super::{expr::*, name::*, value::*, *};
 
 mod fragment;
 mod fragment_concat;
+mod fragment_dbg;
+mod fragment_doc;
 mod fragment_expr;
 mod fragment_for;
 mod fragment_if;
 mod fragment_let;
 mod fragment_name;
+mod fragment_str;
+mod fragment_trace;
 pub use fragment::*;
 pub use fragment_concat::*;
+pub use fragment_dbg::*;
+pub use fragment_doc::*;
 pub use fragment_expr::*;
 pub use fragment_for::*;
 pub use fragment_if::*;
 pub use fragment_let::*;
 pub use fragment_name::*;
+pub use fragment_str::*;
+pub use fragment_trace::*;
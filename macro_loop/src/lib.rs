@@ -1,5 +1,7 @@
 #![doc = "`macro_loop!` macro crate"]
 #![deny(missing_docs)]
+#![allow(clippy::module_inception)]
+#![cfg_attr(feature = "nightly", feature(proc_macro_diagnostic))]
 
 mod expr;
 mod fragment;
@@ -9,8 +11,22 @@ mod value;
 mod util;
 use util::*;
 
+#[cfg(test)]
+mod errortests;
+#[cfg(test)]
+mod listtests;
+#[cfg(test)]
+mod panictests;
+#[cfg(test)]
+mod precedencetests;
+#[cfg(test)]
+mod prettytests;
 #[cfg(test)]
 mod speedtests;
+#[cfg(test)]
+mod stringtests;
+#[cfg(test)]
+mod warningtests;
 
 /// `macro_loop!` provides special fragment features using `@`.
 ///
@@ -21,6 +37,7 @@ mod speedtests;
 /// For loops emit their body per value:
 ///
 /// ```rust
+/// # use macro_loop::macro_loop;
 /// macro_loop! {
 ///     @for N in 2..=4 {
 ///         struct @[Vec @N];
@@ -43,11 +60,27 @@ mod speedtests;
 /// * an identifier,
 /// * an array of values.
 ///
-/// Values support operators such as `+`, `..` and `==`.
+/// Values support operators such as `+`, `..`, `==` and `in`,
+/// with the same precedence and associativity as in Rust
+/// (`in` binds like a comparison).
+/// Lists are concatenated with `+`, lists and strings are repeated with `*`,
+/// and `in` tests whether a value is an item of a list (`@c in [x, y]`)
+/// or a substring of a string (`"sub" in "substring"`).
+///
+/// Lists are indexed with `[]`, where negative indices count from the end (`@xs[-1]`),
+/// and sliced with ranges that may leave out either bound (`@xs[2..]`, `@xs[..@N]`, `@xs[1..-1]`).
+/// Ranges count down when their start is greater than their end (`5..0` is `[5, 4, 3, 2, 1]`),
+/// and `(0..16).step_by(4)` keeps every 4th item.
+///
+/// Int arithmetic is checked like in Rust, so overflow and division by zero are errors,
+/// and ranges, repeats and combinations are limited to about a million items.
 ///
 /// Declaring a for loop with multiple parameters (`@for a in [...], b in [...]`),
 /// emits the body per value combination.
 ///
+/// Errors inside a loop note the values of the failing iteration
+/// (``while expanding `@for X in ...` with X = w``).
+///
 /// # If Statements
 ///
 /// Syntax: `@if <condition> { ... }`
@@ -55,6 +88,7 @@ mod speedtests;
 /// An if statement emits its body only if its condition is met:
 ///
 /// ```rust
+/// # use macro_loop::macro_loop;
 /// macro_rules! not_equal {
 ///     ($a:ident $b:ident) => {
 ///         macro_loop! {
@@ -80,16 +114,24 @@ mod speedtests;
 ///
 /// Let statements declare names that have a value:
 ///
+/// ```rust,ignore
 /// macro_loop! {
 ///     @let components = [x, y, z, w];
 ///
-///     @for X in @components, Y in @components] {
+///     @for X in @components, Y in @components {
 ///         ...
 ///     }
 /// }
+/// ```
 ///
 /// The `<name>` needs to be a pattern, and the ~value~ has to match it.
 ///
+/// Like in Rust, there is a warning for a name that is never used,
+/// unless it starts with an underscore.
+/// There is also a warning for a name that shadows a name from an outer scope,
+/// like a nested `@for x` inside `@for x`.
+/// Both are reported on stable too, in any position that can hold them (see [Debugging](#debugging)).
+///
 /// # Identifiers
 ///
 /// Syntax: `@[<idents>]`
@@ -97,25 +139,328 @@ mod speedtests;
 /// Concats identifier segments into a single identifier:
 ///
 /// ```rust
-/// @let N = 5;
+/// # use macro_loop::macro_loop;
+/// macro_loop! {
+///     @let N = 5;
+///
+///     struct @[Struct @N]; // Struct5
+/// }
+/// ```
+///
+/// Concats that make a keyword of any edition emit a raw identifier (`@[ty pe]` is `r#type`),
+/// and so do string methods on identifiers, while identifiers written in the template
+/// are emitted as written.
+/// Making `self`, `Self`, `super` or `crate`, which can't be raw,
+/// or something that isn't a valid identifier (`1abc`, `a-b`) is an error.
+///
+/// Segments are identifiers, literals (`@[Vec 3]`) or fragments.
+/// Ending the concat with `=> <kind>` makes it output something other than an identifier:
+/// * `str`, `raw_str`, `bytes` (`b"..."`), `cstr` (`c"..."`), `char`,
+/// * `int`, `float` (`@[@N 0 => int]`), which have to be valid literals,
+/// * `lifetime` (`@[a @N => lifetime]` is `'a5`),
+/// * `path`, where `::` separates the path's segments (`@[std :: vec :: @T => path]`).
+///
+/// A case modifier before the segments converts the result's case:
+/// `@[snake: @Type _getter]` makes `vec3_f_getter` out of `Vec3F`.
+/// The cases are `snake`, `camel`, `pascal`, `screaming_snake` and `kebab`.
+/// Leading and trailing underscores are kept (`_private_thing` is `_PrivateThing` in pascal),
+/// and since `-` can't be part of an identifier,
+/// `kebab` only works with a kind like `=> str` (`@[kebab: Foo Bar => str]` is `"foo-bar"`).
+///
+/// # Interpolation
+///
+/// Syntax: `@"..."`, `@/// ...`, `@//! ...`
+///
+/// Strings and doc comments marked with `@` evaluate the `{@<expr>}`s inside them:
+///
+/// ```rust
+/// # use macro_loop::macro_loop;
+/// struct Vec4(f32, f32, f32, f32);
+///
+/// macro_loop! {
+///     impl Vec4 {
+///         @for [idx, X] in [x, y, z, w].enumerate() {
+///             @/// Returns the `{@X}` component, at index {@idx}.
+///             pub fn @X(&self) -> f32 {
+///                 self.@idx
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// Values are formatted like with `format` (see [Functions](#functions)),
+/// which also supports format specs (`{@N:02}`).
+/// `{{` and `}}` emit `{` and `}`, and other braces are an error, like in `format!`.
+///
+/// # Methods
+///
+/// Syntax: `<value>.<method>(<args>)`
+///
+/// Values have methods that compute new values:
+///
+/// ```rust
+/// # use macro_loop::macro_loop;
+/// macro_loop! {
+///     @let components = [x, y, z, w];
+///
+///     @for c in @components.take(3).reverse() {
+///         fn @c() {}
+///     }
+/// }
+/// ```
+///
+/// List methods:
+/// * `len()`, `is_empty()`,
+/// * `first()`, `last()`, `contains(value)`, `position(value)`,
+/// * `reverse()`, `skip(n)`, `take(n)`, `step_by(n)`, `enumerate()`,
+/// * `flatten()`, `concat(lists...)`, `repeat(n)`,
+/// * `zip(lists...)`, `unzip()`, `transpose()`, `interleave(lists...)`,
+///   which require lists of the same length,
+/// * `windows(n)`, `chunks(n)`, `rotate(n)` (to the left, or to the right for negative `n`).
+///
+/// Combinatorics methods return lists of lists, ready to be destructured with list patterns.
+/// Their output is in lexicographic order of item positions, like Python's `itertools`:
+/// * `combinations(k)` - `[a, b, c].combinations(2)` is `[[a, b], [a, c], [b, c]]`,
+/// * `combinations_with_replacement(k)` - also `[a, a]`, `[b, b]` and `[c, c]`,
+/// * `permutations(k)` - every ordering of `k` distinct items (all of them when `k` is omitted),
+/// * `powerset()` - every subset, shortest first: `[[], [a], [b], [a, b]]`.
+///
+/// Aggregation methods work on lists of ints or lists of floats, but not on a mix of both:
+/// * `sum()`, `product()`,
+/// * `running_sum()` - exclusive prefix sums, so field sizes `[4, 2, 8]` give offsets `[0, 4, 6]`,
+/// * `min()`, `max()` - the smallest or largest item of a non-empty list
+///   (`a.min(b)` and `a.max(b)` still compare two values),
+/// * `all()`, `any()` - on lists of bools,
+/// * `count(value)`.
+///
+/// Sorting and set methods work on lists of any values.
+/// Values of different kinds are sorted by kind (bools, ints, floats, chars, strings, idents, lists),
+/// and lists are sorted lexicographically:
+/// * `sort()`, `sort_desc()`,
+/// * `dedup()` - removes consecutive duplicates,
+/// * `unique()` - removes all duplicates, keeping the first occurrence,
+/// * `union(list)`, `intersection(list)`, `difference(list)` - without duplicates, in the order of `self`,
+/// * `is_subset(list)`.
+///
+/// String methods work on strings and idents alike, and return the same kind they are called on.
+/// Their patterns may be strings, idents or chars:
+/// * `len()`, `is_empty()` - in bytes, like Rust,
+/// * `starts_with(pat)`, `ends_with(pat)`, `contains(pat)`, `find(pat)`,
+/// * `replace(from, to)`, `trim()`, `to_upper()`, `to_lower()`,
+/// * `strip_prefix(pat)`, `strip_suffix(pat)` - which fail when the pattern is missing
+///   (`get_mut.strip_suffix(_mut)` is `get`),
+/// * `split(sep)` - where the numeric parts of an ident become ints (`u8x16.split(x)` is `[u8, 16]`),
+/// * `is_keyword()`, `is_valid_ident()`,
+/// * `chars()` - a list of chars (`xyzw.chars()` is `['x', 'y', 'z', 'w']`), `char_at(idx)`.
+///
+/// Strings and idents are indexed and sliced by byte indices like lists (`@name[1..3]`, `@name[-1]`),
+/// where an index into the middle of a char is an error.
+///
+/// Case methods convert strings and idents, keeping their kind:
+/// `to_snake_case()`, `to_camel_case()`, `to_pascal_case()`,
+/// `to_screaming_snake_case()` and `to_kebab_case()`.
+/// Words are split at non-alphanumeric characters and at case changes,
+/// keeping acronyms and digits together (`HTTPServer` is `http_server`, `Vec3F` is `vec3_f`).
+///
+/// Closure methods take closures, written `|<patterns>| <expr>`,
+/// which can use the names in scope where the closure is written:
+///
+/// ```rust
+/// # use macro_loop::macro_loop;
+/// macro_loop! {
+///     @let vectors = (2..=4).map(|N| @[Vec @N]);
+///
+///     @for Vec in @vectors {
+///         struct @Vec;
+///     }
+/// }
+/// ```
+///
+/// * `map(f)`, `filter(f)`, `flat_map(f)`, `fold(init, |acc, item| ...)`,
+/// * `filter_map(f)` - where `f` returns `[value]` to keep `value`, or `[]` to skip the item,
+/// * `any_by(f)`, `all_by(f)`, `sort_by_key(f)`.
+///
+/// # Functions
+///
+/// Syntax: `<function>(<args>)`
+///
+/// * `product(lists...)` - the cartesian product, in the order of nested `@for` loops:
+///   `product([a, b], [x, y])` is `[[a, x], [a, y], [b, x], [b, y]]`,
+/// * `format(template, args...)` - a string formatted like Rust's `format!`,
+///   also callable as `template.format(args...)`.
+///
+/// `format` supports positional (`{}`, `{1}`) and named (`{N}`) arguments,
+/// where names are taken from the surrounding `@let` and `@for` names,
+/// and Rust's format specs (`{:02}`, `{:>8}`, `{:#x}`, `{:.2}`, `{:w$}`).
+/// Concat turns the string into an identifier:
+///
+/// ```rust
+/// # use macro_loop::macro_loop;
+/// macro_loop! {
+///     @for T in [f32, f64], N in [2, 4] {
+///         struct @[@(format("Vec3_{T}_x{}", @N))]; // Vec3_f32_x2, ...
+///     }
+/// }
+/// ```
+///
+/// # Debugging
+///
+/// Syntax: `@dbg(<expr>)`, `@trace { ... }`
+///
+/// `@dbg` reports the kind and value of an expression as a warning, and emits nothing.
+/// `@trace` reports every fragment evaluated inside it with its output,
+/// including each iteration of a loop:
 ///
-/// struct @[Struct @N]; // Struct5
+/// ```rust
+/// # use macro_loop::macro_loop;
+/// macro_loop! {
+///     @let N = 3;
+///     @dbg([x, y, z, w][0..@N]) // `[x, y, z, w][0..@N]` = list `[x, y, z]`
+///
+///     @trace {
+///         @for X in [x, y] {
+///             struct @[Vec @X]; // `@[Vec @X]` => `Vecx`, ...
+///         }
+///     }
+/// }
+/// ```
+///
+/// `dbg` and `trace` can still be bound, but `@dbg(...)` and `@trace { ... }` always start these fragments.
+///
+/// Proc macros can't emit warnings on stable, so warnings are reported as deprecation warnings
+/// of items that are added next to the output's items or statements,
+/// to the first `fn` body in it, or around it if it is an expression.
+/// Where none of these compile, like in a call that only outputs a type,
+/// `@dbg` and `@trace` fail with their output instead.
+/// With the `nightly` feature, warnings are real warnings, but that needs a nightly compiler.
+///
+/// To see the whole output, set the `MACRO_LOOP_DUMP` environment variable to a directory.
+/// Every `macro_loop!` call then writes its formatted output to a file in it,
+/// named after the call site, like `src_main_rs_3_1-<hash>.rs`.
+/// A relative directory is relative to where the compiler runs, which is the workspace root with Cargo.
+/// Cargo doesn't know about the variable, so a crate has to be rebuilt for it to take effect:
+///
+/// ```sh
+/// touch src/lib.rs && MACRO_LOOP_DUMP=target/macro_loop cargo build
 /// ```
+///
+/// # Errors
+///
+/// Every failing fragment is reported, not just the first one.
+/// The names of a failing `@let` can still be used without reporting more errors.
+/// With the default `partial-expansion` feature,
+/// the output of the fragments that succeeded is still emitted after the errors,
+/// so IDEs keep seeing the items that an error doesn't affect.
+/// That output is dropped if a failing fragment leaves a hole in its syntax,
+/// like in `struct @[Foo @bad];`, so that the error isn't hidden by a parse error.
 #[proc_macro]
 pub fn macro_loop(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     use syn::parse::Parser;
 
-    match macro_loop2.parse2(input.into()) {
-        Ok(stream) => stream,
-        Err(err) => err.into_compile_error(),
-    }
-    .into()
+    let input = proc_macro2::TokenStream::from(input);
+
+    let output = if cfg!(feature = "partial-expansion") {
+        macro_loop_partial(input.clone())
+    } else {
+        match macro_loop2.parse2(input.clone()) {
+            Ok(stream) => stream,
+            Err(err) => err.into_compile_error(),
+        }
+    };
+
+    dump_expansion(&input, &output);
+
+    name::warn_unused_bindings();
+    let output = emit_warnings(output, take_warnings());
+
+    output.into()
 }
 
 fn macro_loop2(input: syn::parse::ParseStream) -> syn::Result<proc_macro2::TokenStream> {
     use syn::parse::Parse;
 
+    reset_expansion_state();
+
     let name_stream = name::NameStream::parse(input)?;
 
     name_stream.resolve(&name::Namespace::new())
 }
+
+/// Expands to a `compile_error!` per error, followed by everything that succeeds,
+/// so that IDEs still see the items that aren't affected by an error.
+///
+/// Parse and evaluation errors are reported together, in the order of the template.
+fn macro_loop_partial(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    use syn::parse::Parser;
+
+    reset_expansion_state();
+
+    let name_stream = match name::NameStream::parse_recovering.parse2(input) {
+        Ok(name_stream) => name_stream,
+        Err(err) => return err.into_compile_error(),
+    };
+
+    let mut errors = Errors::new();
+    let output = name_stream.resolve_recovering(&name::Namespace::new(), &mut errors);
+
+    match errors.finish() {
+        Ok(()) => output,
+
+        Err(err) => {
+            let mut errors = err.into_compile_error();
+
+            // a failing fragment in a syntax position (`struct @[Foo @bad];`) leaves a hole,
+            // whose parse error would replace the actual errors
+            if is_complete_syntax(&output) {
+                errors.extend(output);
+            }
+
+            errors
+        }
+    }
+}
+
+/// Forgets the bindings and warnings that an earlier expansion on this thread left behind,
+/// which happens if it panicked before reporting them.
+fn reset_expansion_state() {
+    name::reset_bindings();
+    take_warnings();
+}
+
+/// Whether tokens parse on their own as items, statements or an expression.
+///
+/// Output that starts with an item is in item position, where a hole can't hide
+/// as an empty statement (`struct A; ; struct B;`), so it has to parse as items.
+fn is_complete_syntax(tokens: &proc_macro2::TokenStream) -> bool {
+    use syn::parse::{ParseStream, Parser};
+
+    fn all<T: syn::parse::Parse>(input: ParseStream) -> syn::Result<()> {
+        while !input.is_empty() {
+            input.parse::<T>()?;
+        }
+
+        Ok(())
+    }
+
+    let item_parsers: [fn(ParseStream) -> syn::Result<()>; 3] = [
+        all::<syn::Item>,
+        all::<syn::ImplItem>,
+        all::<syn::TraitItem>,
+    ];
+    let other_parsers: [fn(ParseStream) -> syn::Result<()>; 2] = [
+        |input| syn::Block::parse_within(input).map(drop),
+        |input| input.parse::<syn::Expr>().map(drop),
+    ];
+
+    let parses =
+        |parser: &fn(ParseStream) -> syn::Result<()>| parser.parse2(tokens.clone()).is_ok();
+    let starts_with_item = (|input: ParseStream| {
+        input.parse::<syn::Item>()?;
+        input.parse::<proc_macro2::TokenStream>()
+    })
+    .parse2(tokens.clone())
+    .is_ok();
+
+    item_parsers.iter().any(parses) || (!starts_with_item && other_parsers.iter().any(parses))
+}
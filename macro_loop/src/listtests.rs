@@ -0,0 +1,354 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+
+use crate::macro_loop2;
+
+#[test]
+fn test_negative_indices() {
+    assert_eq!(eval(quote! { [a, b, c][-1] }), "c");
+    assert_eq!(eval(quote! { [a, b, c][-3] }), "a");
+    assert_eq!(eval(quote! { [a, b, c][[0, -1]] }), "[a,c]");
+}
+
+#[test]
+fn test_open_slices() {
+    assert_eq!(eval(quote! { [a, b, c, d][1..] }), "[b,c,d]");
+    assert_eq!(eval(quote! { [a, b, c, d][..2] }), "[a,b]");
+    assert_eq!(eval(quote! { [a, b, c, d][..] }), "[a,b,c,d]");
+    assert_eq!(eval(quote! { [a, b, c, d][1..-1] }), "[b,c]");
+    assert_eq!(eval(quote! { [a, b, c, d][-2..] }), "[c,d]");
+    assert_eq!(eval(quote! { [a, b, c, d][..=1] }), "[a,b]");
+}
+
+#[test]
+fn test_descending_ranges() {
+    assert_eq!(eval(quote! { 5..0 }), "[5,4,3,2,1]");
+    assert_eq!(eval(quote! { 3..=0 }), "[3,2,1,0]");
+    assert_eq!(eval(quote! { 2..2 }), "[]");
+    assert_eq!(eval(quote! { (0..16).step_by(4) }), "[0,4,8,12]");
+    assert_eq!(eval(quote! { (10..0).step_by(3) }), "[10,7,4,1]");
+}
+
+#[test]
+fn test_core_methods() {
+    assert_eq!(eval(quote! { [a, b, c].len() }), "3");
+    assert_eq!(eval(quote! { [].is_empty() }), "true");
+    assert_eq!(eval(quote! { [a, b, c].first() }), "a");
+    assert_eq!(eval(quote! { [a, b, c].last() }), "c");
+    assert_eq!(eval(quote! { [a, b, c].contains(b) }), "true");
+    assert_eq!(eval(quote! { [a, b, c].position(c) }), "2");
+    assert_eq!(eval(quote! { [a, b, c].reverse() }), "[c,b,a]");
+    assert_eq!(eval(quote! { [a, b, c].skip(1).take(1) }), "[b]");
+    assert_eq!(eval(quote! { [a, b].enumerate() }), "[[0,a],[1,b]]");
+    assert_eq!(eval(quote! { [[a], [b, c]].flatten() }), "[a,b,c]");
+    assert_eq!(eval(quote! { [a].concat([b], [c]) }), "[a,b,c]");
+    assert_eq!(eval(quote! { [a, b].repeat(2) }), "[a,b,a,b]");
+}
+
+#[test]
+fn test_reshaping_methods() {
+    assert_eq!(eval(quote! { [a, b].zip([x, y]) }), "[[a,x],[b,y]]");
+    assert_eq!(eval(quote! { [[a, x], [b, y]].unzip() }), "[[a,b],[x,y]]");
+    assert_eq!(
+        eval(quote! { [[a, b], [c, d]].transpose() }),
+        "[[a,c],[b,d]]"
+    );
+    assert_eq!(eval(quote! { [a, b].interleave([x, y]) }), "[a,x,b,y]");
+    assert_eq!(eval(quote! { [a, b, c].windows(2) }), "[[a,b],[b,c]]");
+    assert_eq!(eval(quote! { [a, b, c].chunks(2) }), "[[a,b],[c]]");
+    assert_eq!(eval(quote! { [a, b, c].rotate(1) }), "[b,c,a]");
+    assert_eq!(eval(quote! { [a, b, c].rotate(-1) }), "[c,a,b]");
+}
+
+#[test]
+fn test_sorting_and_sets() {
+    assert_eq!(eval(quote! { [3, 1, 2].sort() }), "[1,2,3]");
+    assert_eq!(eval(quote! { [3, 1, 2].sort_desc() }), "[3,2,1]");
+    assert_eq!(
+        eval(quote! { [b, 1, "s", a, true].sort() }),
+        "[true,1,\"s\",a,b]"
+    );
+    assert_eq!(
+        eval(quote! { [[1, 2], [1], [0, 5]].sort() }),
+        "[[0,5],[1],[1,2]]"
+    );
+    assert_eq!(eval(quote! { [a, a, b, a].dedup() }), "[a,b,a]");
+    assert_eq!(eval(quote! { [a, a, b, a].unique() }), "[a,b]");
+    assert_eq!(eval(quote! { [a, b, a].union([c, b]) }), "[a,b,c]");
+    assert_eq!(eval(quote! { [a, b, c].intersection([c, a]) }), "[a,c]");
+    assert_eq!(eval(quote! { [a, b, c].difference([b]) }), "[a,c]");
+    assert_eq!(eval(quote! { [a, c].is_subset([a, b, c]) }), "true");
+}
+
+#[test]
+fn test_aggregate_methods() {
+    assert_eq!(eval(quote! { [1, 2, 3].sum() }), "6");
+    assert_eq!(eval(quote! { [1.5, 2.0].sum() }), "3.5");
+    assert_eq!(eval(quote! { [].sum() }), "0");
+    assert_eq!(eval(quote! { [2, 3, 4].product() }), "24");
+    assert_eq!(eval(quote! { [0.5, 3.0].product() }), "1.5");
+    assert_eq!(eval(quote! { [].product() }), "1");
+    assert_eq!(eval(quote! { [4, 2, 8].running_sum() }), "[0,4,6]");
+    assert_eq!(eval(quote! { [0.5, 1.5].running_sum() }), "[0.0,0.5]");
+    assert_eq!(eval(quote! { [].running_sum() }), "[]");
+
+    assert_eq!(eval(quote! { [3, 1, 2].min() }), "1");
+    assert_eq!(eval(quote! { [3, 1, 2].max() }), "3");
+    assert_eq!(eval(quote! { [1.5, -2.0].min() }), "-2.0");
+    assert_eq!(eval(quote! { [b, c, a].max() }), "c");
+    assert_eq!(eval(quote! { 3.min(5) }), "3");
+    assert_eq!(eval(quote! { 3.max(5) }), "5");
+
+    assert_eq!(eval(quote! { [true, false].any() }), "true");
+    assert_eq!(eval(quote! { [true, false].all() }), "false");
+    assert_eq!(eval(quote! { [].any() }), "false");
+    assert_eq!(eval(quote! { [].all() }), "true");
+    assert_eq!(eval(quote! { [a, b, a].count(a) }), "2");
+    assert_eq!(eval(quote! { [].count(a) }), "0");
+}
+
+#[test]
+fn test_aggregate_errors() {
+    assert_eq!(
+        eval_error(quote! { [170141183460469231731687303715884105727, 1].sum() }),
+        "integer overflow"
+    );
+    assert_eq!(
+        eval_error(quote! { [170141183460469231731687303715884105727, 2].product() }),
+        "integer overflow"
+    );
+    assert_eq!(
+        eval_error(quote! { [170141183460469231731687303715884105727, 1, 0].running_sum() }),
+        "integer overflow"
+    );
+    assert_eq!(
+        eval_error(quote! { [1, 2.0].sum() }),
+        "expected an int like the first item, found float `2.0`"
+    );
+    assert_eq!(
+        eval_error(quote! { [1.0, 2].product() }),
+        "expected a float like the first item, found int `2`"
+    );
+    assert_eq!(
+        eval_error(quote! { [1, 2.5].max() }),
+        "cannot compare float `2.5` with int `1`"
+    );
+    assert_eq!(eval_error(quote! { [].min() }), "the list is empty");
+    assert_eq!(
+        eval_error(quote! { [true, 1].all() }),
+        "expected a bool, found int `1`"
+    );
+}
+
+#[test]
+fn test_combinatorics_order() {
+    assert_eq!(
+        eval(quote! { [a, b, c].combinations(2) }),
+        "[[a,b],[a,c],[b,c]]"
+    );
+    assert_eq!(
+        eval(quote! { [a, b].combinations_with_replacement(2) }),
+        "[[a,a],[a,b],[b,b]]"
+    );
+    assert_eq!(
+        eval(quote! { [a, b, c].permutations() }),
+        "[[a,b,c],[a,c,b],[b,a,c],[b,c,a],[c,a,b],[c,b,a]]"
+    );
+    assert_eq!(
+        eval(quote! { [a, b, c].permutations(2) }),
+        "[[a,b],[a,c],[b,a],[b,c],[c,a],[c,b]]"
+    );
+    assert_eq!(
+        eval(quote! { [a, b, c].powerset() }),
+        "[[],[a],[b],[c],[a,b],[a,c],[b,c],[a,b,c]]"
+    );
+    assert_eq!(
+        eval(quote! { product([a, b], [x, y]) }),
+        "[[a,x],[a,y],[b,x],[b,y]]"
+    );
+}
+
+#[test]
+fn test_closure_methods() {
+    assert_eq!(eval(quote! { [1, 2, 3].map(|x| @x * 2) }), "[2,4,6]");
+    assert_eq!(eval(quote! { [1, 2, 3].filter(|x| @x != 2) }), "[1,3]");
+    assert_eq!(eval(quote! { [1, 2].flat_map(|x| [@x, @x]) }), "[1,1,2,2]");
+    assert_eq!(eval(quote! { [1, 2, 3].fold(0, |sum, x| @sum + @x) }), "6");
+    assert_eq!(
+        eval(quote! { [[a, 2], [b, 1]].sort_by_key(|[_name, key]| @key) }),
+        "[[b,1],[a,2]]"
+    );
+}
+
+#[test]
+fn test_closure_scoping() {
+    // the closure uses the `k` where it is written, not the one where it is called
+    assert_eq!(
+        expand(quote! {
+            @let k = 1;
+            @let f = |x| @x + @k;
+
+            @for k in [10] {
+                @([1].map(@f)[0]) @k
+            }
+        }),
+        "210"
+    );
+
+    assert_eq!(
+        expand(quote! {
+            @let k = 1;
+            @let f = |x| format("{}{k}", @x);
+
+            @for k in [2] {
+                @([a].map(@f)[0]) @k
+            }
+        }),
+        "\"a1\"2"
+    );
+}
+
+#[test]
+fn test_unknown_function() {
+    assert_eq!(
+        eval_error(quote! { prodcut([a], [b]) }),
+        "unknown function `prodcut`. did you mean `product`?"
+    );
+}
+
+#[test]
+fn test_suggestions() {
+    assert_eq!(
+        eval_error(quote! { [a, b].revrese() }),
+        "unknown method `revrese`. did you mean `reverse`?"
+    );
+    assert_eq!(
+        eval_error(quote! { "ab".LEN() }),
+        "unknown method `LEN`. did you mean `len`?"
+    );
+    assert_eq!(
+        eval_error(quote! { [a, b].frobnicate() }),
+        "unknown method `frobnicate`"
+    );
+
+    let error = |template| match macro_loop2.parse2(template) {
+        Ok(output) => panic!("expanded to `{output}` instead of an error"),
+        Err(err) => err.to_string(),
+    };
+    assert_eq!(
+        error(quote! { @let items = [a]; @for x in @itmes { @x } }),
+        "cannot find `itmes`. did you mean `items`?"
+    );
+    assert_eq!(
+        error(quote! { @let N = 1; @n }),
+        "cannot find `n`. did you mean `N`?"
+    );
+    assert_eq!(error(quote! { @let N = 1; @xyz }), "cannot find `xyz`");
+}
+
+#[test]
+fn test_error_notes() {
+    let error = |template| match macro_loop2.parse2(template) {
+        Ok(output) => panic!("expanded to `{output}` instead of an error"),
+        Err(err) => err.to_string(),
+    };
+
+    assert_eq!(
+        error(quote! { @for N in [1, 0] { @(1 / @N) } }),
+        "attempt to divide by zero\nnote: while expanding `@for N in ...` with N = 0"
+    );
+    assert_eq!(
+        error(quote! { @for x in [a], [i, j] in [[b, 2]] { @if true { @missing } } }),
+        "cannot find `missing`\n\
+         note: while expanding `@if ...`\n\
+         note: while expanding `@for x in ..., [i, j] in ...` with x = a, [i, j] = [b, 2]"
+    );
+}
+
+#[test]
+fn test_failed_let() {
+    let error = |template| match macro_loop2.parse2(template) {
+        Ok(output) => panic!("expanded to `{output}` instead of an error"),
+        Err(err) => err
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>(),
+    };
+
+    assert_eq!(
+        error(quote! {
+            @let zero = 0;
+            @let [x, y] = [1 / @zero, 2];
+            @x @y @(@x + 1)
+            @for i in 0..@y { @i }
+            @missing
+        }),
+        ["attempt to divide by zero", "cannot find `missing`"]
+    );
+    assert_eq!(
+        error(quote! { @let [x, y] = [1]; @let f = |a| @x; @([1].map(@f)) }),
+        ["value doesn't match pattern `[x, y]`. expected a list of 2 items, found list `[1]`"]
+    );
+}
+
+#[test]
+fn test_error_cap() {
+    let names = (0..20).map(|idx| quote::format_ident!("x{idx}"));
+
+    let error = match macro_loop2.parse2(quote! { #(@#names)* }) {
+        Ok(output) => panic!("expanded to `{output}` instead of an error"),
+        Err(err) => err
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>(),
+    };
+
+    assert_eq!(error.len(), 17);
+    assert_eq!(error[0], "cannot find `x0`");
+    assert_eq!(error[15], "cannot find `x15`");
+    assert_eq!(error[16], "4 more errors were not reported");
+}
+
+#[test]
+fn test_closure_errors() {
+    assert_eq!(
+        eval_error(quote! { [1].filter_map(|x| @x) }),
+        "expected the closure to return `[]` or `[value]`, found int `1`"
+    );
+    assert_eq!(
+        eval_error(quote! { [1].flat_map(|x| @x) }),
+        "expected the closure to return a list, found int `1`"
+    );
+    assert_eq!(
+        eval_error(quote! { [1].filter(|x| a) }),
+        "expected the closure to return a bool, found ident `a`"
+    );
+    assert_eq!(
+        eval_error(quote! { [1, a].max() }),
+        "cannot compare ident `a` with int `1`"
+    );
+}
+
+fn eval(expr: TokenStream) -> String {
+    macro_loop2
+        .parse2(quote! { @(#expr) })
+        .unwrap_or_else(|err| panic!("`{expr}`: {err}"))
+        .to_string()
+        .replace(' ', "")
+}
+
+fn expand(template: TokenStream) -> String {
+    macro_loop2
+        .parse2(template.clone())
+        .unwrap_or_else(|err| panic!("`{template}`: {err}"))
+        .to_string()
+        .replace(' ', "")
+}
+
+fn eval_error(expr: TokenStream) -> String {
+    match macro_loop2.parse2(quote! { @(#expr) }) {
+        Ok(output) => panic!("`{expr}` expanded to `{output}` instead of an error"),
+        Err(err) => err.to_string(),
+    }
+}
@@ -1,10 +1,12 @@
-use super::{fragment::*, value::*};
+use super::{fragment::*, value::*, *};
 
 mod name;
 mod name_stream;
 mod namespace;
 mod pattern;
+mod usage;
 pub use name::*;
 pub use name_stream::*;
 pub use namespace::*;
 pub use pattern::*;
+pub use usage::*;
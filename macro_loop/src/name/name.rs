@@ -7,11 +7,16 @@ use syn::{
     parse::{Parse, ParseStream},
 };
 
+use super::*;
+
 #[derive(Debug, Clone, Display)]
-#[display("id")]
+#[display("{id}")]
 pub struct Name {
     id: NameId,
     span: Span,
+    /// The site where the name is bound, if it is a binding.
+    /// Clones keep the site, so every binding by the same pattern counts as one.
+    site: BindingSite,
 }
 
 #[repr(transparent)]
@@ -21,6 +26,14 @@ pub struct NameId {
 }
 
 impl Name {
+    pub fn new(id: &str, span: Span) -> Self {
+        Self {
+            id: NameId { inner: id.into() },
+            span,
+            site: BindingSite::unique(),
+        }
+    }
+
     pub fn span(&self) -> Span {
         self.span
     }
@@ -28,6 +41,16 @@ impl Name {
     pub fn id(&self) -> &NameId {
         &self.id
     }
+
+    pub fn site(&self) -> BindingSite {
+        self.site
+    }
+}
+
+impl NameId {
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
 }
 
 impl Deref for Name {
@@ -47,6 +70,7 @@ impl Parse for Name {
                 inner: ident.to_string().into_boxed_str(),
             },
             span: ident.span(),
+            site: BindingSite::unique(),
         })
     }
 }
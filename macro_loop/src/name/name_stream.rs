@@ -4,7 +4,7 @@ use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
 use quote::{ToTokens, TokenStreamExt};
 use syn::{
     Token,
-    parse::{Parse, ParseStream, Parser},
+    parse::{Parse, ParseStream, Parser, Peek, discouraged::Speculative},
     token::{Brace, Bracket, Paren},
 };
 
@@ -17,7 +17,23 @@ pub struct NameStream {
 enum NameStreamSegment {
     TokenStream(TokenStream),
     Group(NameStreamGroup),
-    Fragment(Frag),
+    Fragment(NameStreamFragment),
+    Error(NameStreamError),
+}
+
+/// A fragment with its source, which `@trace` reports.
+struct NameStreamFragment {
+    span: Span,
+    source: TokenStream,
+    frag: Frag,
+}
+
+/// A fragment that failed to parse, which is reported in order with the evaluation errors.
+struct NameStreamError {
+    error: syn::Error,
+    /// The pattern of a failing `@let`, whose names are poisoned
+    /// so that their uses don't repeat the error.
+    pat: Option<Pattern>,
 }
 
 struct NameStreamGroup {
@@ -28,21 +44,65 @@ struct NameStreamGroup {
 
 impl Parse for NameStream {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let output = Self::parse_recovering(input)?;
+
+        let mut errors = Errors::new();
+        output.parse_errors(&mut errors);
+        errors.finish()?;
+
+        Ok(output)
+    }
+}
+
+impl NameStream {
+    /// Parses the stream, recording fragments that fail to parse instead of stopping at them,
+    /// so that [`Self::resolve_recovering`] reports their errors where they are.
+    /// A failing fragment is skipped up to where it would end (see [`skip_fragment`]).
+    pub fn parse_recovering(input: ParseStream) -> syn::Result<Self> {
         let mut output = Self { segs: Vec::new() };
 
         let mut tokenstream = TokenStream::new();
 
         while !input.is_empty() {
             if input.peek(Token![@]) {
-                let _ = input.parse::<Token![@]>().unwrap();
+                let at_token = input.parse::<Token![@]>().unwrap();
 
                 output
                     .segs
                     .push(NameStreamSegment::TokenStream(take(&mut tokenstream)));
 
-                let fragment = input.parse::<Frag>()?;
+                let start = input.cursor();
+                let fork = input.fork();
+                match fork.parse::<Frag>() {
+                    Ok(frag) => {
+                        input.advance_to(&fork);
 
-                output.segs.push(NameStreamSegment::Fragment(fragment));
+                        let mut source = TokenStream::new();
+                        let mut cursor = start;
+                        while cursor != input.cursor() {
+                            let (token, next) = cursor.token_tree().unwrap();
+                            source.append(token);
+                            cursor = next;
+                        }
+
+                        output
+                            .segs
+                            .push(NameStreamSegment::Fragment(NameStreamFragment {
+                                span: at_token.span,
+                                source,
+                                frag,
+                            }));
+                    }
+
+                    Err(error) => {
+                        let pat = fork_let_pattern(input);
+                        skip_fragment(input)?;
+
+                        output
+                            .segs
+                            .push(NameStreamSegment::Error(NameStreamError { error, pat }));
+                    }
+                }
             } else if input.peek(Brace) || input.peek(Paren) || input.peek(Bracket) {
                 let group = input.parse::<Group>().unwrap();
 
@@ -50,10 +110,12 @@ impl Parse for NameStream {
                     .segs
                     .push(NameStreamSegment::TokenStream(take(&mut tokenstream)));
 
+                let stream = Self::parse_recovering.parse2(group.stream())?;
+
                 output.segs.push(NameStreamSegment::Group(NameStreamGroup {
                     span: group.span(),
                     delim: group.delimiter(),
-                    stream: Box::new(NameStream::parse.parse2(group.stream())?),
+                    stream: Box::new(stream),
                 }));
             } else {
                 tokenstream.append(input.parse::<TokenTree>()?);
@@ -66,10 +128,31 @@ impl Parse for NameStream {
 
         Ok(output)
     }
-}
 
-impl NameStream {
+    /// Records the errors of the fragments that failed to parse.
+    fn parse_errors(&self, errors: &mut Errors) {
+        for seg in &self.segs {
+            match seg {
+                NameStreamSegment::Group(group) => group.stream.parse_errors(errors),
+                NameStreamSegment::Error(error) => errors.push(error.error.clone()),
+                NameStreamSegment::TokenStream(_) | NameStreamSegment::Fragment(_) => {}
+            }
+        }
+    }
+
+    /// Resolves the stream, skipping the output of failing fragments
+    /// and reporting their errors together.
     pub fn resolve(&self, namespace: &Namespace) -> syn::Result<TokenStream> {
+        let mut errors = Errors::new();
+        let output = self.resolve_recovering(namespace, &mut errors);
+        errors.finish()?;
+
+        Ok(output)
+    }
+
+    /// Resolves the stream, recording the errors of failing fragments and skipping their output.
+    /// Groups keep the output of their successful fragments.
+    pub fn resolve_recovering(&self, namespace: &Namespace, errors: &mut Errors) -> TokenStream {
         let mut output = TokenStream::new();
 
         let mut namespace = namespace.fork();
@@ -79,18 +162,105 @@ impl NameStream {
                 NameStreamSegment::TokenStream(stream) => stream.to_tokens(&mut output),
 
                 NameStreamSegment::Group(group) => {
-                    let mut token = Group::new(group.delim, group.stream.resolve(&namespace)?);
+                    let stream = group.stream.resolve_recovering(&namespace, errors);
+
+                    let mut token = Group::new(group.delim, stream);
                     token.set_span(group.span);
 
                     output.append(token);
                 }
 
-                NameStreamSegment::Fragment(frag) => frag.apply(&mut namespace, &mut output)?,
+                NameStreamSegment::Fragment(fragment) => {
+                    let mut frag_output = TokenStream::new();
+                    let result = fragment.frag.apply(&mut namespace, &mut frag_output);
+
+                    trace_fragment(
+                        fragment.span,
+                        &fragment.source,
+                        result.as_ref().map(|()| &frag_output),
+                    );
+
+                    match result {
+                        Ok(()) => output.extend(frag_output),
+                        Err(err) => errors.push(err),
+                    }
+                }
+
+                NameStreamSegment::Error(error) => {
+                    errors.push(error.error.clone());
+
+                    for name in error.pat.iter().flat_map(Pattern::names) {
+                        namespace.poison(name);
+                    }
+                }
             }
 
             namespace.flush();
         }
 
-        Ok(output)
+        output
     }
 }
+
+/// The pattern of a `@let` that failed to parse after it.
+fn fork_let_pattern(input: ParseStream) -> Option<Pattern> {
+    let fork = input.fork();
+    fork.parse::<Token![let]>().ok()?;
+
+    fork.parse().ok()
+}
+
+/// Skips the tokens of a fragment that failed to parse, keeping the tokens after it:
+/// `@let` ends at its `;`, `@for`, `@if` and `@trace` end at their body,
+/// and other fragments are a single token or a call like `@dbg(...)`.
+fn skip_fragment(input: ParseStream) -> syn::Result<()> {
+    let Ok(first) = input.fork().parse::<TokenTree>() else {
+        return Ok(());
+    };
+
+    match first {
+        TokenTree::Ident(ident) if ident == "let" => skip_past(
+            input,
+            |token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ';'),
+        ),
+        TokenTree::Ident(ident) if ident == "for" || ident == "trace" => {
+            skip_past(input, is_brace_group)
+        }
+        TokenTree::Ident(ident) if ident == "if" => {
+            skip_past(input, is_brace_group)?;
+            while input.peek(Token![else]) {
+                skip_past(input, is_brace_group)?;
+            }
+
+            Ok(())
+        }
+
+        TokenTree::Ident(ident) if ident == "dbg" => skip_call(input, Paren),
+        TokenTree::Punct(punct) if punct.as_char() == '#' => skip_call(input, Bracket),
+
+        _ => input.parse::<TokenTree>().map(drop),
+    }
+}
+
+fn skip_past(input: ParseStream, is_end: impl Fn(&TokenTree) -> bool) -> syn::Result<()> {
+    while !input.is_empty() {
+        if is_end(&input.parse::<TokenTree>()?) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn skip_call(input: ParseStream, group: impl Peek) -> syn::Result<()> {
+    input.parse::<TokenTree>()?;
+    if input.peek(group) {
+        input.parse::<TokenTree>()?;
+    }
+
+    Ok(())
+}
+
+fn is_brace_group(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace)
+}
@@ -6,8 +6,19 @@ use super::*;
 
 pub struct Namespace<'p, 'v> {
     parent: Option<&'p Namespace<'p, 'v>>,
-    names: HashMap<NameId, ValueRef<'v>>,
-    new_names: HashMap<NameId, ValueRef<'v>>,
+    names: HashMap<NameId, Binding<'v>>,
+    new_names: HashMap<NameId, Binding<'v>>,
+}
+
+struct Binding<'v> {
+    /// `None` if the binding is poisoned, because evaluating its value failed.
+    value: Option<ValueRef<'v>>,
+    site: BindingSite,
+}
+
+/// Copies of the bindings that a closure uses from where it is written.
+pub struct Captures {
+    bindings: Vec<(NameId, Option<Value<'static>>, BindingSite)>,
 }
 
 impl<'p, 'v> Namespace<'p, 'v> {
@@ -19,7 +30,31 @@ impl<'p, 'v> Namespace<'p, 'v> {
         }
     }
 
-    pub fn fork(&self) -> Namespace {
+    /// A namespace of captured bindings, which a closure's body is evaluated in.
+    ///
+    /// The bindings keep their sites, so reading them counts as using the original bindings.
+    pub fn with_captures(captures: &'v Captures) -> Self {
+        let names = captures
+            .bindings
+            .iter()
+            .map(|(id, value, site)| {
+                let binding = Binding {
+                    value: value.as_ref().map(ValueRef::Ref),
+                    site: *site,
+                };
+
+                (id.clone(), binding)
+            })
+            .collect();
+
+        Self {
+            parent: None,
+            names,
+            new_names: HashMap::new(),
+        }
+    }
+
+    pub fn fork(&self) -> Namespace<'_, '_> {
         Namespace {
             parent: Some(self),
             names: HashMap::new(),
@@ -27,28 +62,125 @@ impl<'p, 'v> Namespace<'p, 'v> {
         }
     }
 
+    /// Binds `name` once the namespace is flushed,
+    /// warning if it shadows a name from a parent namespace.
     pub fn insert(&mut self, name: &Name, value: ValueRef<'v>) -> syn::Result<()> {
-        match self.new_names.insert(name.id().clone(), value) {
+        let shadows = self
+            .parent
+            .is_some_and(|parent| parent.lookup(name).is_some());
+
+        let binding = Binding {
+            value: Some(value),
+            site: bind_site(name, shadows),
+        };
+
+        match self.new_names.insert(name.id().clone(), binding) {
             None => Ok(()),
             Some(_) => Err(Error::new(name.span(), format!("duplicate name `{name}`"))),
         }
     }
 
+    /// Binds `name` to nothing once the namespace is flushed, after its value failed.
+    ///
+    /// Reading a poisoned name fails without reporting another error,
+    /// instead of every later use reporting that the name can't be found.
+    pub fn poison(&mut self, name: &Name) {
+        let site = bind_site(name, false);
+        // the binding's error is already reported, so it isn't also unused
+        use_site(site);
+
+        let binding = Binding { value: None, site };
+        self.new_names.insert(name.id().clone(), binding);
+    }
+
     pub fn flush(&mut self) {
         for (k, v) in self.new_names.drain() {
             self.names.insert(k, v);
         }
     }
 
-    pub fn get(&self, name: &Name) -> syn::Result<&Value> {
-        if let Some(value) = self.new_names.get(name) {
-            Ok(value)
-        } else if let Some(value) = self.names.get(name) {
-            Ok(value)
-        } else if let Some(parent) = self.parent {
-            parent.get(name)
+    pub fn get(&self, name: &Name) -> syn::Result<&Value<'_>> {
+        match self.lookup(name) {
+            Some(binding) => {
+                use_site(binding.site);
+
+                binding.value.as_deref().ok_or_else(|| {
+                    caused_by_earlier_error(name.span(), format!("`{name}` is poisoned"))
+                })
+            }
+
+            None => {
+                let suggestion = suggest(name.as_str(), self.visible_names());
+
+                Err(Error::new(
+                    name.span(),
+                    format!("cannot find `{name}`{}", did_you_mean(suggestion)),
+                ))
+            }
+        }
+    }
+
+    fn lookup(&self, name: &Name) -> Option<&Binding<'_>> {
+        if let Some(binding) = self.new_names.get(name) {
+            Some(binding)
+        } else if let Some(binding) = self.names.get(name) {
+            Some(binding)
         } else {
-            Err(Error::new(name.span(), format!("cannot find {name}")))
+            self.parent?.lookup(name)
+        }
+    }
+
+    /// Copies the visible bindings whose names `is_captured` accepts.
+    pub fn capture(&self, is_captured: impl Fn(&str) -> bool) -> Captures {
+        let mut bindings = Vec::new();
+        self.capture_into(&is_captured, &mut bindings);
+
+        Captures { bindings }
+    }
+
+    fn capture_into(
+        &self,
+        is_captured: &impl Fn(&str) -> bool,
+        bindings: &mut Vec<(NameId, Option<Value<'static>>, BindingSite)>,
+    ) {
+        for (id, binding) in self.new_names.iter().chain(&self.names) {
+            // a binding shadows the bindings of the same name in the parents
+            if is_captured(id.as_str()) && !bindings.iter().any(|(captured, ..)| captured == id) {
+                let value = binding.value.as_ref().map(|value| value.to_static());
+                bindings.push((id.clone(), value, binding.site));
+            }
+        }
+
+        if let Some(parent) = self.parent {
+            parent.capture_into(is_captured, bindings);
+        }
+    }
+
+    /// The names of this namespace and of its parents.
+    fn visible_names(&self) -> Vec<&str> {
+        let mut names = self
+            .new_names
+            .keys()
+            .chain(self.names.keys())
+            .map(NameId::as_str)
+            .collect::<Vec<_>>();
+
+        if let Some(parent) = self.parent {
+            names.extend(parent.visible_names());
+        }
+
+        names
+    }
+}
+
+impl Captures {
+    /// Records that the captured bindings whose names `is_read` accepts are used,
+    /// since a closure uses them where it is written, even if it is never called.
+    pub fn use_sites(&self, is_read: impl Fn(&str) -> bool) {
+        for (id, _, site) in &self.bindings {
+            if is_read(id.as_str()) {
+                use_site(*site);
+            }
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt::{self, Display, Formatter};
+
 use derive_syn_parse::Parse;
 use syn::{Error, Ident, Token, punctuated::Punctuated, token::Bracket};
 
@@ -13,6 +15,16 @@ pub enum Pattern {
     List(PatternList),
 }
 
+impl Pattern {
+    /// The names that the pattern binds.
+    pub fn names(&self) -> Vec<&Name> {
+        match self {
+            Self::Ident(name) => vec![name],
+            Self::List(list) => list.items.iter().flat_map(Self::names).collect(),
+        }
+    }
+}
+
 impl<'p, 'v> Namespace<'p, 'v> {
     pub fn insert_pat(&mut self, pat: &Pattern, value: ValueRef<'v>) -> syn::Result<()> {
         match pat {
@@ -21,13 +33,7 @@ impl<'p, 'v> Namespace<'p, 'v> {
             Pattern::List(self_) => match value {
                 ValueRef::Owned(Value::List(value)) => {
                     if self_.items.len() != value.items.len() {
-                        return Err(Error::new_spanned(
-                            value,
-                            format!(
-                                "value doesn't match pattern. expected {} items",
-                                self_.items.len()
-                            ),
-                        ));
+                        return Err(mismatch(pat, &Value::List(value)));
                     }
 
                     for (pat_item, value) in self_.items.iter().zip(value.items) {
@@ -37,13 +43,7 @@ impl<'p, 'v> Namespace<'p, 'v> {
 
                 ValueRef::Ref(Value::List(value)) => {
                     if self_.items.len() != value.items.len() {
-                        return Err(Error::new_spanned(
-                            value,
-                            format!(
-                                "value doesn't match pattern. expected {} items",
-                                self_.items.len()
-                            ),
-                        ));
+                        return Err(mismatch(pat, &Value::List(value.clone())));
                     }
 
                     for (pat_item, value) in self_.items.iter().zip(&value.items) {
@@ -51,12 +51,7 @@ impl<'p, 'v> Namespace<'p, 'v> {
                     }
                 }
 
-                _ => {
-                    return Err(Error::new_spanned(
-                        &*value,
-                        "value doesn't match pattern. expected a list",
-                    ));
-                }
+                _ => return Err(mismatch(pat, &value)),
             },
         }
 
@@ -64,6 +59,21 @@ impl<'p, 'v> Namespace<'p, 'v> {
     }
 }
 
+fn mismatch(pat: &Pattern, value: &Value) -> Error {
+    let expected = match pat {
+        Pattern::Ident(_) => "a value".to_string(),
+        Pattern::List(list) => format!("a list of {} items", list.items.len()),
+    };
+
+    Error::new_spanned(
+        value,
+        format!(
+            "value doesn't match pattern `{pat}`. {}",
+            value.expected(&expected)
+        ),
+    )
+}
+
 #[derive(Clone, Parse)]
 struct PatternList {
     #[bracket]
@@ -73,3 +83,16 @@ struct PatternList {
     #[call(Punctuated::parse_terminated)]
     items: Punctuated<Pattern, Token![,]>,
 }
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Ident(name) => write!(f, "{name}"),
+            Self::List(list) => {
+                let items = list.items.iter().map(ToString::to_string);
+
+                write!(f, "[{}]", items.collect::<Vec<_>>().join(", "))
+            }
+        }
+    }
+}
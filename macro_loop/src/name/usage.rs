@@ -0,0 +1,123 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use super::*;
+
+thread_local! {
+    static BINDINGS: RefCell<Bindings> = RefCell::new(Bindings::default());
+    static NEXT_SITE: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Where a name is bound in the template, by a counter that is assigned when its `Name` is made.
+///
+/// A loop binds the same name in a new namespace on every iteration,
+/// so the site is what's unused, not each of the bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BindingSite(usize);
+
+impl BindingSite {
+    /// A site that is different from every other site on this thread.
+    pub fn unique() -> Self {
+        let site = NEXT_SITE.get();
+        NEXT_SITE.set(site + 1);
+
+        Self(site)
+    }
+}
+
+#[derive(Default)]
+struct Bindings {
+    indices: HashMap<BindingSite, usize>,
+    /// The sites in the order they are first bound in, so the warnings are in a stable order.
+    sites: Vec<SiteUsage>,
+}
+
+struct SiteUsage {
+    name: Name,
+    used: bool,
+    shadows: bool,
+}
+
+/// Records a binding of `name`,
+/// and warns the first time the site shadows a binding from an outer namespace.
+pub fn bind_site(name: &Name, shadows: bool) -> BindingSite {
+    let site = name.site();
+
+    BINDINGS.with_borrow_mut(|bindings| {
+        let idx = *bindings.indices.entry(site).or_insert_with(|| {
+            warn_fragment_name(name);
+
+            bindings.sites.push(SiteUsage {
+                name: name.clone(),
+                used: false,
+                shadows: false,
+            });
+
+            bindings.sites.len() - 1
+        });
+
+        let usage = &mut bindings.sites[idx];
+        if shadows && !usage.shadows && !is_ignored(name) {
+            usage.shadows = true;
+            warn(
+                name.span(),
+                format!("`{name}` shadows a binding of the same name from an outer scope"),
+            );
+        }
+    });
+
+    site
+}
+
+/// Records that a binding of the site was read.
+pub fn use_site(site: BindingSite) {
+    BINDINGS.with_borrow_mut(|bindings| {
+        if let Some(&idx) = bindings.indices.get(&site) {
+            bindings.sites[idx].used = true;
+        }
+    });
+}
+
+/// Forgets every site, so that an expansion doesn't see the sites of an earlier one
+/// that failed before [`warn_unused_bindings`].
+pub fn reset_bindings() {
+    BINDINGS.take();
+}
+
+/// Warns about the sites that were never read, and forgets every site.
+pub fn warn_unused_bindings() {
+    for usage in BINDINGS.take().sites {
+        if !usage.used && !is_ignored(&usage.name) {
+            let name = &usage.name;
+
+            warn(
+                name.span(),
+                format!(
+                    "unused binding `{name}`. if this is intentional, prefix it with an underscore: `_{name}`"
+                ),
+            );
+        }
+    }
+}
+
+/// Warns about binding a name that starts a fragment,
+/// since the fragment is still parsed instead of reading the name.
+fn warn_fragment_name(name: &Name) {
+    let fragment = match name.as_str() {
+        "dbg" => "`@dbg(...)` still debugs its input",
+        "trace" => "`@trace { ... }` still traces its body",
+        _ => return,
+    };
+
+    warn(
+        name.span(),
+        format!("`{name}` is bound, but {fragment} instead of reading it"),
+    );
+}
+
+/// Like in Rust, names that start with an underscore are never warned about.
+fn is_ignored(name: &Name) -> bool {
+    name.as_str().starts_with('_')
+}
@@ -0,0 +1,123 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+
+use crate::macro_loop2;
+
+/// Asserts that `macro_loop` reports an error for a template instead of panicking.
+macro_rules! assert_error {
+    ($name:ident: $($template:tt)*) => {
+        #[test]
+        fn $name() {
+            let template = quote! { $($template)* };
+
+            if let Ok(output) = expand(template.clone()) {
+                panic!("`{template}` expanded to `{output}` instead of an error");
+            }
+        }
+    };
+}
+
+/// Asserts that `macro_loop` expands a template without panicking, even if the output is an error.
+macro_rules! assert_no_panic {
+    ($name:ident: $($template:tt)*) => {
+        #[test]
+        fn $name() {
+            let _ = expand(quote! { $($template)* });
+        }
+    };
+}
+
+// Ints
+
+assert_error!(add_overflow: @(170141183460469231731687303715884105727 + 1));
+assert_error!(sub_overflow: @(0 - 170141183460469231731687303715884105727 - 2));
+assert_error!(mul_overflow: @(170141183460469231731687303715884105727 * 2));
+assert_error!(div_by_zero: @(1 / 0));
+assert_error!(rem_by_zero: @(1 % 0));
+assert_error!(div_overflow: @((0 - 170141183460469231731687303715884105727 - 1) / -1));
+assert_error!(rem_overflow: @((0 - 170141183460469231731687303715884105727 - 1) % -1));
+assert_error!(neg_overflow: @(-(0 - 170141183460469231731687303715884105727 - 1)));
+assert_error!(shl_overflow: @(1 << 128));
+assert_error!(shr_overflow: @(1 >> 128));
+assert_error!(shl_negative: @(1 << -1));
+assert_error!(int_literal_overflow: @(170141183460469231731687303715884105728 + 0));
+assert_error!(sum_overflow: @([170141183460469231731687303715884105727, 1].sum()));
+assert_error!(product_overflow: @([170141183460469231731687303715884105727, 2].product()));
+
+// Floats
+
+assert_error!(float_div_by_zero: @(1.0 / 0.0));
+assert_error!(float_nan: @(0.0 / 0.0));
+assert_error!(float_overflow: @(1e300 * 1e300));
+assert_error!(float_product_overflow: @([1e300, 1e300].product()));
+assert_error!(float_running_sum_overflow: @([1e308, 1e308, 1.0].running_sum()));
+
+// Sizes
+
+assert_error!(huge_range: @(0..1 << 100));
+assert_error!(huge_range_inclusive: @(0..=170141183460469231731687303715884105727));
+assert_error!(huge_reverse_range: @(170141183460469231731687303715884105727..0));
+assert_error!(huge_list_repeat: @([1, 2] * (1 << 100)));
+assert_error!(huge_str_repeat: @("ab" * (1 << 62)));
+assert_error!(negative_repeat: @([1] * -1));
+assert_error!(huge_repeat_method: @([1, 2].repeat(1 << 62)));
+assert_error!(huge_powerset: @((0..64).powerset()));
+assert_error!(huge_permutations: @((0..100).permutations()));
+assert_error!(huge_combinations: @((0..100).combinations(50)));
+assert_error!(huge_product: @(product(0..64, 0..64, 0..64, 0..64)));
+assert_error!(huge_format_width: @(format("{:1000000000000}", 1)));
+assert_error!(huge_format_precision: @(format("{:.1000000000000}", 1.0)));
+
+// Indices
+
+assert_error!(huge_index: @([1, 2][170141183460469231731687303715884105727]));
+assert_error!(negative_index: @([1, 2][-5]));
+assert_error!(huge_char_index: @("abc".char_at(1 << 100)));
+assert_error!(huge_slice: @([1, 2][0..170141183460469231731687303715884105727]));
+assert_error!(str_slice_inside_char: @("é"[1..]));
+assert_no_panic!(huge_step: @([1, 2].step_by(1 << 100)));
+assert_no_panic!(min_rotate: @([1, 2].rotate(0 - 170141183460469231731687303715884105727 - 1)));
+
+// Strings
+
+assert_error!(invalid_utf8_byte_str: @let x = b"\xff"; @[a @x]);
+assert_error!(invalid_utf8_cstr: @let x = c"\xff"; @[a @x]);
+assert_error!(raw_str_with_cr: @[@("a\rb") => raw_str]);
+assert_error!(cstr_with_nul: @[@("a\0b") => cstr]);
+assert_error!(unterminated_interpolation: @"{@x");
+assert_error!(unterminated_format: @(format("{", 1)));
+assert_error!(unmatched_format: @(format("}")));
+assert_error!(unknown_format_trait: @(format("{:y}", 1)));
+
+// Identifiers
+
+assert_error!(invalid_concat: @[@("a b")]);
+assert_error!(empty_concat: @[@("")]);
+assert_error!(digit_concat: @[@(1) a]);
+assert_error!(invalid_lifetime: @[@("a b") => lifetime]);
+assert_error!(invalid_path: @[a :: @("1") => path]);
+assert_error!(invalid_ident_value: @(a.replace("a", "1")));
+assert_error!(empty_ident_value: @(a.replace("a", "")));
+assert_error!(keyword_ident_value: @(a.replace("a", "self")));
+assert_error!(empty_ident_split: @(a__b.split(_)));
+assert_error!(empty_ident_strip: @(get.strip_prefix(get)));
+
+// Syntax
+
+assert_error!(unknown_name: @x);
+assert_error!(unknown_method: @([1].frobnicate()));
+assert_error!(missing_for_body: @for x in 0..3);
+assert_error!(empty_expr: @());
+assert_error!(closure_arity: @([1].map(|a, b| a)));
+
+fn expand(template: TokenStream) -> syn::Result<String> {
+    catch_unwind(AssertUnwindSafe(|| {
+        macro_loop2
+            .parse2(template.clone())
+            .map(|output| output.to_string())
+    }))
+    .unwrap_or_else(|_| panic!("`{template}` panicked"))
+}
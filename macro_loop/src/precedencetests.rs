@@ -0,0 +1,102 @@
+#![allow(
+    clippy::precedence,
+    clippy::identity_op,
+    clippy::nonminimal_bool,
+    clippy::bad_bit_mask,
+    clippy::overly_complex_bool_expr
+)]
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+
+use crate::macro_loop2;
+
+/// Asserts that `macro_loop` evaluates an expression to the same value as Rust.
+macro_rules! assert_rust_order {
+    ($($expr:tt)*) => {
+        assert_eq!(
+            eval(quote! { $($expr)* }),
+            ($($expr)*).to_string(),
+            "`{}`",
+            stringify!($($expr)*),
+        )
+    };
+}
+
+/// Asserts that `macro_loop` evaluates a range expression to the same items as Rust.
+macro_rules! assert_rust_range {
+    ($($expr:tt)*) => {
+        assert_eq!(
+            eval(quote! { $($expr)* }),
+            format!("{:?}", ($($expr)*).collect::<Vec<_>>()).replace(' ', ""),
+            "`{}`",
+            stringify!($($expr)*),
+        )
+    };
+}
+
+/// Checks `a op1 b op2 c` for every pair of `op1` and `op2`.
+macro_rules! test_op_pairs {
+    ($name:ident: $a:literal [$($op1:tt)*] $b:literal [$($op2:tt)*] $c:literal) => {
+        #[test]
+        fn $name() {
+            test_op_pairs!(@op1 $a [$($op1)*] $b [$($op2)*] $c);
+        }
+    };
+
+    (@op1 $a:literal [$($op1:tt)*] $b:literal $ops2:tt $c:literal) => {
+        $(test_op_pairs!(@op2 $a $op1 $b $ops2 $c);)*
+    };
+
+    (@op2 $a:literal $op1:tt $b:literal [$($op2:tt)*] $c:literal) => {
+        $(assert_rust_order!($a $op1 $b $op2 $c);)*
+    };
+}
+
+test_op_pairs!(test_arithmetic_pairs: 200 [* / % + - << >> & ^ |] 7 [* / % + - << >> & ^ |] 3);
+
+test_op_pairs!(test_arithmetic_comparison_pairs: 200 [* / % + - << >> & ^ |] 7 [== != < > <= >=] 3);
+
+test_op_pairs!(test_comparison_arithmetic_pairs: 200 [== != < > <= >=] 7 [* / % + - << >> & ^ |] 3);
+
+test_op_pairs!(test_logical_pairs: true [&& || & | ^] false [&& || & | ^] true);
+
+#[test]
+fn test_comparison_logical_pairs() {
+    assert_rust_order!(200 == 7 && 7 != 3);
+    assert_rust_order!(200 != 7 || 7 == 3);
+    assert_rust_order!(200 < 7 && 7 > 3);
+    assert_rust_order!(200 > 7 || 7 < 3);
+    assert_rust_order!(200 <= 7 && 7 >= 3 || 3 == 3);
+    assert_rust_order!(200 >= 7 || 7 <= 3 && 3 != 3);
+    assert_rust_order!(true == false || false != true);
+}
+
+#[test]
+fn test_ranges() {
+    assert_rust_range!(0..2 + 3);
+    assert_rust_range!(1 + 1..2 * 3);
+    assert_rust_range!(0..=2 * 2);
+    assert_rust_range!(0..8 >> 1);
+    assert_rust_range!(0..1 | 6);
+    assert_rust_range!(10 % 4..10 - 4);
+}
+
+#[test]
+fn test_non_associative() {
+    assert!(eval_result(quote! { 1 < 2 < 3 }).is_err());
+    assert!(eval_result(quote! { 1 == 2 == true }).is_err());
+    assert!(eval_result(quote! { 1 <= 2 != true }).is_err());
+    assert!(eval_result(quote! { 0..1..2 }).is_err());
+}
+
+fn eval(expr: TokenStream) -> String {
+    eval_result(expr.clone()).unwrap_or_else(|err| panic!("`{expr}`: {err}"))
+}
+
+fn eval_result(expr: TokenStream) -> syn::Result<String> {
+    let output = macro_loop2.parse2(quote! { @(#expr) })?;
+
+    Ok(output.to_string().replace(' ', ""))
+}
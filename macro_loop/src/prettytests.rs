@@ -0,0 +1,107 @@
+use quote::quote;
+
+use crate::util::pretty_print;
+
+#[test]
+fn test_items() {
+    assert_eq!(
+        pretty_print(quote! {
+            #[derive(Clone)]
+            pub struct Vec2 { x: f32, y: f32 }
+            impl Vec2 { pub fn x(&self) -> f32 { self.x } }
+        }),
+        "\
+#[derive(Clone)]
+pub struct Vec2 {
+    x: f32,
+    y: f32
+}
+
+impl Vec2 {
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+}
+"
+    );
+}
+
+#[test]
+fn test_statements() {
+    assert_eq!(
+        pretty_print(quote! {
+            fn f(a: &[u8]) {
+                let b = -a[0] as i32 * 2;
+                if b >= 0 { g(b); } else { return; }
+                for i in 0..=b {}
+            }
+        }),
+        "\
+fn f(a: &[u8]) {
+    let b = -a[0] as i32 * 2;
+    if b >= 0 {
+        g(b);
+    } else {
+        return;
+    }
+    for i in 0..=b {}
+}
+"
+    );
+}
+
+#[test]
+fn test_generics_and_closures() {
+    assert_eq!(
+        pretty_print(
+            quote! { fn f<T: Into<Vec<u8>>>(x: T) -> usize { x.into().iter().map(|b| *b as usize).sum() } }
+        ),
+        "\
+fn f<T: Into<Vec<u8>>>(x: T) -> usize {
+    x.into().iter().map(|b| *b as usize).sum()
+}
+"
+    );
+    assert_eq!(
+        pretty_print(quote! { const A: bool = 1 < 2 && 3 > 2; }),
+        "const A: bool = 1 < 2 && 3 > 2;\n"
+    );
+}
+
+#[test]
+fn test_paths_and_macros() {
+    assert_eq!(
+        pretty_print(quote! { use std::{fmt, mem::take}; static S: &str = ::core::stringify!(a); }),
+        "\
+use std::{fmt, mem::take};
+
+static S: &str = ::core::stringify!(a);
+"
+    );
+    assert_eq!(
+        pretty_print(quote! { fn f<'a>(x: &'a mut u8) -> Option<&'a u8> { x?; None } }),
+        "\
+fn f<'a>(x: &'a mut u8) -> Option<&'a u8> {
+    x?;
+    None
+}
+"
+    );
+}
+
+#[test]
+fn test_match() {
+    assert_eq!(
+        pretty_print(quote! { fn f(x: u8) -> u8 { match x { 0 => 1, _ => { x } } } }),
+        "\
+fn f(x: u8) -> u8 {
+    match x {
+        0 => 1,
+        _ => {
+            x
+        }
+    }
+}
+"
+    );
+}
@@ -0,0 +1,304 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+
+use crate::macro_loop2;
+
+#[test]
+fn test_search_methods() {
+    assert_eq!(eval(quote! { "vec3".len() }), "4");
+    assert_eq!(eval(quote! { get_mut.starts_with(get) }), "true");
+    assert_eq!(eval(quote! { get_mut.ends_with("_mut") }), "true");
+    assert_eq!(eval(quote! { "abc".contains('b') }), "true");
+    assert_eq!(eval(quote! { "abc".find(c) }), "2");
+    assert_eq!(eval(quote! { get_ref.replace(ref, mut) }), "get_mut");
+    assert_eq!(eval(quote! { "_a_".trim() }), "\"_a_\"");
+    assert_eq!(eval(quote! { Vec.to_upper() }), "VEC");
+    assert_eq!(eval(quote! { get_mut.strip_suffix(_mut) }), "get");
+    assert_eq!(eval(quote! { u8x16.split(x) }), "[u8,16]");
+    assert_eq!(eval(quote! { "a,b".split(',') }), "[\"a\",\"b\"]");
+    assert_eq!(eval(quote! { "type".is_keyword() }), "true");
+    assert!(expand_error(quote! { @(get.strip_suffix(_mut)) }).contains("_mut"));
+}
+
+#[test]
+fn test_chars_and_slices() {
+    assert_eq!(eval(quote! { xyzw.chars() }), "['x','y','z','w']");
+    assert_eq!(eval(quote! { xyzw.char_at(1) }), "'y'");
+    assert_eq!(eval(quote! { xyzw[1..3] }), "yz");
+    assert_eq!(eval(quote! { xyzw[-1] }), "'w'");
+    assert_eq!(eval(quote! { "xyzw"[..2] }), "\"xy\"");
+    assert_eq!(expand(quote! { @for c in xyz.chars() { @c } }), "'x''y''z'");
+}
+
+#[test]
+fn test_format_args() {
+    assert_eq!(eval_str(quote! { format("{}-{}", a, 1) }), "a-1");
+    assert_eq!(eval_str(quote! { format("{1}{0}{1}", a, b) }), "bab");
+    assert_eq!(eval_str(quote! { "{}{{}}".format(x) }), "x{}");
+    assert_eq!(
+        eval_str(quote! { format("{:?} {:?} {:?}", "s", 'c', [a, 1]) }),
+        "\"s\" 'c' [a, 1]"
+    );
+
+    let output = expand(quote! {
+        @for N in [3] {
+            @(format("Vec{N}"))
+        }
+    });
+    assert_eq!(output, "\"Vec3\"");
+
+    let output = expand(quote! {
+        @for w in [3] {
+            @(format("{:0w$}", 7))
+        }
+    });
+    assert_eq!(output, "\"007\"");
+}
+
+#[test]
+fn test_format_specs() {
+    assert_eq!(eval_str(quote! { format("{:02}", 5) }), "05");
+    assert_eq!(
+        eval_str(quote! { format("{:>4}|{:<4}|{:^5}", a, b, c) }),
+        "   a|b   |  c  "
+    );
+    assert_eq!(eval_str(quote! { format("{:*^7}", mid) }), "**mid**");
+    assert_eq!(eval_str(quote! { format("{:+}", 5) }), "+5");
+    assert_eq!(
+        eval_str(quote! { format("{:#x} {:X} {:#b} {:o}", 255, 255, 5, 8) }),
+        "0xff FF 0b101 10"
+    );
+    assert_eq!(eval_str(quote! { format("{:#06x}", 255) }), "0x00ff");
+    assert_eq!(
+        eval_str(quote! { format("{:x} {:#X} {:o} {:#b}", -255, -255, -8, -5) }),
+        "-ff -0xFF -10 -0b101"
+    );
+    assert_eq!(eval_str(quote! { format("{:#07x}", -255) }), "-0x00ff");
+    assert_eq!(eval_str(quote! { format("{:.2}", 1.5) }), "1.50");
+    assert_eq!(eval_str(quote! { format("{:e}", 1500.0) }), "1.5e3");
+    assert_eq!(eval_str(quote! { format("{:1$}", 7, 3) }), "  7");
+}
+
+#[test]
+fn test_format_errors() {
+    let error = |template| expand_error(quote! { @(format(#template, 1)) });
+
+    assert_eq!(
+        error("{"),
+        "unterminated `{` in format string. use `{{` to emit `{`"
+    );
+    assert_eq!(
+        error("{} }"),
+        "unmatched `}` in format string. use `}}` to emit `}`"
+    );
+    assert!(error("{:y}").contains("y"));
+    assert_eq!(error("{:.*}"), "expected a precision after `.`");
+    assert_eq!(error("no args"), "argument never used");
+    assert_eq!(
+        expand_error(quote! { @(format("{}", [a])) }),
+        "list `[a]` cannot be formatted with `{}`. try `{:?}`"
+    );
+}
+
+#[test]
+fn test_interpolation() {
+    let interpolate = |str: &str| expand_str(quote! { @let x = 5; @#str });
+
+    assert_eq!(interpolate("x = {@x}"), "x = 5");
+    assert_eq!(interpolate("{{x}} = {{{@x}}}"), "{x} = {5}");
+    assert_eq!(interpolate("{@x:03}|{@x:>3}|{@x:#b}"), "005|  5|0b101");
+    assert_eq!(interpolate("{@\"a:b\".len():02}"), "03");
+    assert_eq!(interpolate("{@([x, 6][1])}"), "6");
+    assert_eq!(interpolate("{@(format(\"{:?}\", \"a\")):>5}"), "  \"a\"");
+    assert_eq!(interpolate("{@[y _ @x]:>5}"), "  y_5");
+    assert_eq!(interpolate("{@\"}}\".len()}|{@(\"\\\"{\".len())}"), "1|2");
+    assert_eq!(interpolate("{@('}')}|{@('\\'')}|{@(':'):?}"), "}|'|':'");
+}
+
+#[test]
+fn test_interpolation_errors() {
+    let error = |str: &str| expand_error(quote! { @let x = 5; @#str });
+
+    assert_eq!(error("{@x"), "unterminated `{@`. use `{{` to emit `{`");
+    assert_eq!(
+        error("{x}"),
+        "expected `@` after `{` in interpolated string. use `{{` to emit `{`"
+    );
+    assert_eq!(
+        error("x}"),
+        "unmatched `}` in interpolated string. use `}}` to emit `}`"
+    );
+    assert_eq!(
+        error("{@x} }}}"),
+        "unmatched `}` in interpolated string. use `}}` to emit `}`"
+    );
+}
+
+#[test]
+fn test_concat_kinds() {
+    assert_eq!(expand_str(quote! { @[a b => str] }), "ab");
+    assert_eq!(expand(quote! { @[a "\"" => raw_str] }), "r#\"a\"\"#");
+    assert_eq!(expand(quote! { @[a b => bytes] }), "b\"ab\"");
+    assert_eq!(expand(quote! { @[a b => cstr] }), "c\"ab\"");
+    assert_eq!(expand(quote! { @[x => char] }), "'x'");
+    assert_eq!(expand(quote! { @[1 0 u8 => int] }), "10u8");
+    assert_eq!(expand(quote! { @[1 "." 5 => float] }), "1.5");
+    assert_eq!(expand(quote! { @[a 5 => lifetime] }), "'a5");
+    assert_eq!(
+        expand(quote! { @[std :: vec :: Vec => path] }),
+        "std::vec::Vec"
+    );
+    assert_eq!(expand(quote! { @[:: std :: mem => path] }), "::std::mem");
+
+    let output = expand(quote! {
+        @for N in [2, 3] {
+            @[@N 0 => int]
+        }
+    });
+    assert_eq!(output, "2030");
+}
+
+#[test]
+fn test_concat_kind_errors() {
+    assert_eq!(
+        expand_error(quote! { @[a b => char] }),
+        "`ab` is not a single char"
+    );
+    assert_eq!(
+        expand_error(quote! { @[1 a => int] }),
+        "`1a` is not a valid int literal"
+    );
+    assert_eq!(
+        expand_error(quote! { @[1 x => float] }),
+        "`1x` is not a valid float literal"
+    );
+    assert_eq!(
+        expand_error(quote! { @[1 => lifetime] }),
+        "`'1` is not a valid lifetime"
+    );
+    assert_eq!(
+        expand_error(quote! { @[a :: 1 => path] }),
+        "`a::1` is not a valid path"
+    );
+    assert_eq!(
+        expand_error(quote! { @[a :: b] }),
+        "`::` is only allowed in `=> path` concats"
+    );
+    assert_eq!(expand_error(quote! { @[a => list] }), "invalid concat type");
+}
+
+#[test]
+fn test_keyword_idents() {
+    // idents keep the way they are written
+    assert_eq!(expand(quote! { @let k = const; *@k u8 }), "*constu8");
+    assert_eq!(
+        expand(quote! { @for v in [pub, r#type] { @v } }),
+        "pubr#type"
+    );
+
+    // idents made by the macro are raw if they are keywords
+    assert_eq!(expand(quote! { @[ty pe] }), "r#type");
+    assert_eq!(eval(quote! { Type.to_snake_case() }), "r#type");
+    assert_eq!(eval(quote! { ret_urn.replace(_, "") }), "r#return");
+    assert_eq!(eval(quote! { r#async.to_upper().to_lower() }), "r#async");
+    assert_eq!(expand(quote! { @[self :: a => path] }), "self::a");
+}
+
+#[test]
+fn test_invalid_idents() {
+    assert_eq!(
+        eval_error(quote! { a.replace(a, "a b") }),
+        "`a b` is not a valid identifier"
+    );
+    assert_eq!(
+        eval_error(quote! { get.strip_prefix(get) }),
+        "`` is not a valid identifier"
+    );
+    assert_eq!(
+        eval_error(quote! { my_self.strip_prefix(my_) }),
+        "`self` is a keyword which can't be a raw identifier"
+    );
+    assert_eq!(
+        expand_error(quote! { @[Se lf] }),
+        "`Self` is a keyword which can't be a raw identifier"
+    );
+}
+
+#[test]
+fn test_case_methods() {
+    assert_eq!(eval(quote! { HTTPServer.to_snake_case() }), "http_server");
+    assert_eq!(eval(quote! { Vec3F.to_snake_case() }), "vec3_f");
+    assert_eq!(eval(quote! { vec_length.to_camel_case() }), "vecLength");
+    assert_eq!(eval(quote! { vec_length.to_pascal_case() }), "VecLength");
+    assert_eq!(
+        eval(quote! { vecLength.to_screaming_snake_case() }),
+        "VEC_LENGTH"
+    );
+    assert_eq!(
+        eval(quote! { "Vec Length".to_kebab_case() }),
+        "\"vec-length\""
+    );
+}
+
+#[test]
+fn test_case_underscores() {
+    assert_eq!(
+        eval(quote! { _private_thing.to_pascal_case() }),
+        "_PrivateThing"
+    );
+    assert_eq!(eval(quote! { __init__.to_snake_case() }), "__init__");
+    assert_eq!(eval(quote! { type_.to_pascal_case() }), "Type_");
+    assert_eq!(eval(quote! { __.to_pascal_case() }), "__");
+}
+
+#[test]
+fn test_case_modifiers() {
+    assert_eq!(expand(quote! { @[snake: Vec3F _getter] }), "vec3_f_getter");
+    assert_eq!(expand(quote! { @[pascal: vec _ 3] }), "Vec3");
+    assert_eq!(expand(quote! { @[kebab: Foo Bar => str] }), "\"foo-bar\"");
+    assert_eq!(
+        expand_error(quote! { @[kebab: Foo Bar] }),
+        "`kebab:` can't make an identifier. use it with a kind like `=> str`"
+    );
+}
+
+fn eval(expr: TokenStream) -> String {
+    expand(quote! { @(#expr) })
+}
+
+/// Evaluates an expression to a string literal's value.
+fn eval_str(expr: TokenStream) -> String {
+    expand_str(quote! { @(#expr) })
+}
+
+/// Expands a template to a string literal's value, keeping its spaces.
+fn expand_str(template: TokenStream) -> String {
+    let output = macro_loop2
+        .parse2(template.clone())
+        .unwrap_or_else(|err| panic!("`{template}`: {err}"));
+
+    match syn::parse2::<syn::LitStr>(output.clone()) {
+        Ok(lit) => lit.value(),
+        Err(_) => panic!("`{template}` expanded to `{output}` instead of a string"),
+    }
+}
+
+fn expand(template: TokenStream) -> String {
+    macro_loop2
+        .parse2(template.clone())
+        .unwrap_or_else(|err| panic!("`{template}`: {err}"))
+        .to_string()
+        .replace(' ', "")
+}
+
+fn eval_error(expr: TokenStream) -> String {
+    expand_error(quote! { @(#expr) })
+}
+
+fn expand_error(template: TokenStream) -> String {
+    match macro_loop2.parse2(template.clone()) {
+        Ok(output) => panic!("`{template}` expanded to `{output}` instead of an error"),
+        Err(err) => err.to_string(),
+    }
+}
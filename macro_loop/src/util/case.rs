@@ -0,0 +1,109 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Snake,
+    Camel,
+    Pascal,
+    ScreamingSnake,
+    Kebab,
+}
+
+impl Case {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "snake" => Self::Snake,
+            "camel" => Self::Camel,
+            "pascal" => Self::Pascal,
+            "screaming_snake" => Self::ScreamingSnake,
+            "kebab" => Self::Kebab,
+
+            _ => return None,
+        })
+    }
+
+    /// Converts the words of `str` to the case,
+    /// keeping its leading and trailing underscores (`_private_thing` is `_PrivateThing` in pascal).
+    pub fn convert(self, str: &str) -> String {
+        let inner = str.trim_matches('_');
+        if inner.is_empty() {
+            return str.to_string();
+        }
+
+        let prefix = &str[..str.len() - str.trim_start_matches('_').len()];
+        let suffix = &str[str.trim_end_matches('_').len()..];
+
+        format!("{prefix}{}{suffix}", self.convert_words(inner))
+    }
+
+    fn convert_words(self, str: &str) -> String {
+        let words = split_words(str);
+
+        match self {
+            Self::Snake => join(&words, "_", str::to_lowercase),
+            Self::ScreamingSnake => join(&words, "_", str::to_uppercase),
+            Self::Kebab => join(&words, "-", str::to_lowercase),
+            Self::Pascal => join(&words, "", capitalize),
+            Self::Camel => match words.split_first() {
+                Some((first, rest)) => first.to_lowercase() + &join(rest, "", capitalize),
+                None => String::new(),
+            },
+        }
+    }
+}
+
+/// Splits a string into words at non-alphanumeric characters and at case changes.
+///
+/// An uppercase letter starts a word after a lowercase letter (`vecF` is `vec F`),
+/// or when a lowercase letter follows it (`HTTPServer` is `HTTP Server`).
+/// Digits belong to the word before them (`Vec3F` is `Vec3 F`, `u8x16` is one word).
+pub fn split_words(str: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+
+    for run in str.split(|c: char| !c.is_alphanumeric()) {
+        let chars = run.char_indices().collect::<Vec<_>>();
+
+        let mut start = 0;
+        let mut after_lowercase = false;
+        for (i, &(idx, c)) in chars.iter().enumerate() {
+            let before_lowercase = chars
+                .get(i + 1)
+                .is_some_and(|(_, next)| next.is_lowercase());
+
+            if i > 0 && c.is_uppercase() && (after_lowercase || before_lowercase) {
+                words.push(&run[start..idx]);
+                start = idx;
+            }
+
+            if c.is_lowercase() {
+                after_lowercase = true;
+            } else if c.is_uppercase() {
+                after_lowercase = false;
+            }
+        }
+
+        if start < run.len() {
+            words.push(&run[start..]);
+        }
+    }
+
+    words
+}
+
+fn join(words: &[&str], separator: &str, f: impl Fn(&str) -> String) -> String {
+    words
+        .iter()
+        .map(|word| f(word))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
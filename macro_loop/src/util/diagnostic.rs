@@ -0,0 +1,266 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Display,
+};
+
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream, Parser};
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<Warning>> = const { RefCell::new(Vec::new()) };
+    static TRACE_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The most chars of a traced fragment's source or output, after which it is cut off with `...`.
+const MAX_TRACE_LEN: usize = 80;
+
+/// A warning that couldn't be emitted right away,
+/// which is added to the output of the call by [`emit_warnings`].
+pub struct Warning {
+    pub span: Span,
+    pub message: String,
+    /// Whether the warning is output that was asked for, like `@dbg`'s,
+    /// which becomes an error where a warning can't be placed instead of being dropped.
+    pub requested: bool,
+}
+
+/// Reports a compiler warning at `span` once the expansion is done.
+///
+/// With the `nightly` feature, the warning is a real diagnostic that is emitted right away.
+pub fn warn(span: Span, message: impl Display) {
+    push_warning(span, message, false);
+}
+
+/// Reports output that was asked for, like `@dbg`'s, as a warning.
+///
+/// Unlike [`warn`], it is never dropped (see [`emit_warnings`]).
+pub fn report(span: Span, message: impl Display) {
+    push_warning(span, message, true);
+}
+
+fn push_warning(span: Span, message: impl Display, requested: bool) {
+    #[cfg(feature = "nightly")]
+    if proc_macro::is_available() {
+        let level = proc_macro::Level::Warning;
+        proc_macro::Diagnostic::spanned(span.unwrap(), level, message.to_string()).emit();
+
+        return;
+    }
+
+    WARNINGS.with_borrow_mut(|warnings| {
+        warnings.push(Warning {
+            span,
+            message: message.to_string(),
+            requested,
+        });
+    });
+}
+
+/// Takes the warnings that were reported since the last call, and weren't emitted right away.
+pub fn take_warnings() -> Vec<Warning> {
+    WARNINGS.take()
+}
+
+/// Adds warnings to the output of a call.
+///
+/// Proc macros can't emit warnings on stable,
+/// so each warning is the use of a `#[deprecated]` constant whose note is the message.
+/// Those items don't compile in every position, and the position of a call is unknown,
+/// so they are placed where the output shows that they compile:
+/// next to items or statements, at the start of the first `fn` body, or in a block around an expression.
+///
+/// Where none of these apply, like in output that is also a type,
+/// warnings are dropped and requested output becomes a `compile_error!`.
+pub fn emit_warnings(output: TokenStream, warnings: Vec<Warning>) -> TokenStream {
+    if warnings.is_empty() {
+        return output;
+    }
+
+    let items = warnings.iter().map(warning_item).collect::<TokenStream>();
+
+    if is_item_or_statement_position(&output) {
+        return quote! { #output #items };
+    }
+
+    let mut injected = false;
+    let output = inject_into_fn_body(output, &items, &mut injected);
+    if injected {
+        return output;
+    }
+
+    if is_value_expr(&output) {
+        return quote! { { #items #output } };
+    }
+
+    let requested = warnings
+        .iter()
+        .filter(|warning| warning.requested)
+        .collect::<Vec<_>>();
+
+    let Some(first) = requested.first() else {
+        return output;
+    };
+
+    let message = format!(
+        "warnings can't be placed in this position without the `nightly` feature, \
+        so the output of `@dbg` and `@trace` is reported as an error. \
+        move them to a call that outputs items, statements, a `fn` or an expression:\n{}",
+        requested
+            .iter()
+            .map(|warning| warning.message.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+
+    quote_spanned! { first.span => compile_error!(#message) }
+}
+
+fn warning_item(warning: &Warning) -> TokenStream {
+    let message = &warning.message;
+    let use_ident = Ident::new("macro_loop", warning.span);
+
+    quote! {
+        const _: () = {
+            #[deprecated(note = #message)]
+            #[allow(non_upper_case_globals)]
+            const macro_loop: () = ();
+
+            let _ = #use_ident;
+        };
+    }
+}
+
+/// Whether the output can only be items or statements, where `const _` items compile.
+///
+/// Output that is also `impl` or `trait` items doesn't count,
+/// since `const _` isn't allowed there.
+fn is_item_or_statement_position(output: &TokenStream) -> bool {
+    fn all<T: Parse>(input: ParseStream) -> syn::Result<()> {
+        while !input.is_empty() {
+            input.parse::<T>()?;
+        }
+
+        Ok(())
+    }
+
+    if output.is_empty() {
+        return false;
+    }
+
+    let is_items = all::<syn::Item>.parse2(output.clone()).is_ok()
+        && all::<syn::ImplItem>.parse2(output.clone()).is_err()
+        && all::<syn::TraitItem>.parse2(output.clone()).is_err();
+
+    let is_statements = syn::Block::parse_within
+        .parse2(output.clone())
+        .is_ok_and(|stmts| {
+            stmts
+                .iter()
+                .any(|stmt| matches!(stmt, syn::Stmt::Local(_) | syn::Stmt::Expr(_, Some(_))))
+        });
+
+    is_items || is_statements
+}
+
+/// Adds `items` to the start of the first `fn` body in `tokens`,
+/// skipping the input of macro calls.
+fn inject_into_fn_body(
+    tokens: TokenStream,
+    items: &TokenStream,
+    injected: &mut bool,
+) -> TokenStream {
+    let mut output = TokenStream::new();
+
+    let mut after_fn = false;
+    let mut in_signature = false;
+    let mut after_bang = false;
+
+    for mut token in tokens {
+        if let TokenTree::Group(group) = &token
+            && !*injected
+        {
+            let stream = if in_signature && group.delimiter() == Delimiter::Brace {
+                *injected = true;
+                in_signature = false;
+
+                quote! { #items }
+                    .into_iter()
+                    .chain(group.stream())
+                    .collect()
+            } else if !in_signature && !after_bang {
+                inject_into_fn_body(group.stream(), items, injected)
+            } else {
+                group.stream()
+            };
+
+            let mut new_group = Group::new(group.delimiter(), stream);
+            new_group.set_span(group.span());
+            token = TokenTree::Group(new_group);
+        }
+
+        in_signature = match &token {
+            TokenTree::Ident(_) if after_fn => true,
+            TokenTree::Punct(punct) if punct.as_char() == ';' => false,
+            _ => in_signature,
+        };
+        after_fn = matches!(&token, TokenTree::Ident(ident) if ident == "fn");
+        after_bang = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '!');
+
+        output.extend([token]);
+    }
+
+    output
+}
+
+/// Whether the output is an expression, which a block around it doesn't change.
+///
+/// Output that is also a type or a pattern isn't,
+/// and neither are place expressions like `self.field`, which a block would move out of.
+fn is_value_expr(output: &TokenStream) -> bool {
+    fn is_place(expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::Path(_) | syn::Expr::Field(_) | syn::Expr::Index(_) => true,
+            syn::Expr::Unary(expr) => matches!(expr.op, syn::UnOp::Deref(_)),
+            syn::Expr::Paren(expr) => is_place(&expr.expr),
+            syn::Expr::Group(expr) => is_place(&expr.expr),
+            _ => false,
+        }
+    }
+
+    syn::parse2::<syn::Expr>(output.clone()).is_ok_and(|expr| !is_place(&expr))
+        && syn::parse2::<syn::Type>(output.clone()).is_err()
+        && syn::Pat::parse_multi.parse2(output.clone()).is_err()
+}
+
+/// Runs `f` with every fragment it evaluates reported as a warning.
+pub fn trace<T>(f: impl FnOnce() -> T) -> T {
+    TRACE_DEPTH.set(TRACE_DEPTH.get() + 1);
+    let output = f();
+    TRACE_DEPTH.set(TRACE_DEPTH.get() - 1);
+
+    output
+}
+
+/// Reports an evaluated fragment if it is inside a `@trace` block.
+pub fn trace_fragment(span: Span, source: &TokenStream, output: Result<&TokenStream, &syn::Error>) {
+    if TRACE_DEPTH.get() == 0 {
+        return;
+    }
+
+    let source = shorten(format!("@{source}"));
+    match output {
+        Ok(output) => report(
+            span,
+            format!("`{source}` => `{}`", shorten(output.to_string())),
+        ),
+        Err(err) => report(span, format!("`{source}` failed: {err}")),
+    }
+}
+
+fn shorten(text: String) -> String {
+    match text.char_indices().nth(MAX_TRACE_LEN) {
+        Some((idx, _)) => format!("{}...", &text[..idx]),
+        None => text,
+    }
+}
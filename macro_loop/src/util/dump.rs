@@ -0,0 +1,58 @@
+use std::{
+    env, fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use proc_macro2::{Span, TokenStream};
+
+use super::*;
+
+/// The environment variable of the directory that expansions are written to.
+const DUMP_VAR: &str = "MACRO_LOOP_DUMP";
+
+/// Writes the expansion of a `macro_loop!` call to the `MACRO_LOOP_DUMP` directory, if it is set.
+///
+/// The file is named after the call site and a hash of the input,
+/// so calls on the same line, or from the same `macro_rules!`, don't overwrite each other.
+/// Failing to write it is only a warning, since the expansion itself is fine.
+///
+/// This has to be called from inside the proc macro, since it looks at the call site.
+pub fn dump_expansion(input: &TokenStream, output: &TokenStream) {
+    let Some(dir) = env::var_os(DUMP_VAR) else {
+        return;
+    };
+
+    let call_site = proc_macro::Span::call_site();
+    let location = format!(
+        "{}:{}:{}",
+        call_site.file(),
+        call_site.line(),
+        call_site.column()
+    );
+
+    let mut hasher = DefaultHasher::new();
+    input.to_string().hash(&mut hasher);
+
+    let file_name = format!(
+        "{}-{:016x}.rs",
+        location.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+        hasher.finish()
+    );
+
+    let path = Path::new(&dir).join(file_name);
+    let contents = format!(
+        "// `macro_loop!` at {location}\n\n{}",
+        pretty_print(output.clone())
+    );
+
+    if let Err(err) = fs::create_dir_all(&dir).and_then(|()| fs::write(&path, contents)) {
+        report(
+            Span::call_site(),
+            format!(
+                "failed to write the expansion to `{}`: {err}",
+                path.display()
+            ),
+        );
+    }
+}
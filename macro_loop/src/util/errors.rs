@@ -0,0 +1,71 @@
+use std::fmt::Display;
+
+use proc_macro2::Span;
+use syn::Error;
+
+/// The most error messages that are reported together.
+/// Later errors are likely to be consequences of earlier ones.
+pub const MAX_ERRORS: usize = 16;
+
+/// The start of the message of errors that are caused by an earlier error,
+/// which are not reported since they would only repeat it.
+const CAUSED_BY_EARLIER_ERROR: &str = "caused by an earlier error: ";
+
+/// An error that is only a consequence of an earlier error, like using a name whose `@let` failed.
+///
+/// These errors are dropped by [`Errors`].
+pub fn caused_by_earlier_error(span: Span, message: impl Display) -> Error {
+    Error::new(span, format!("{CAUSED_BY_EARLIER_ERROR}{message}"))
+}
+
+/// Collects errors so that parsing and evaluation can keep going after one,
+/// reporting every mistake in a single build.
+pub struct Errors {
+    error: Option<Error>,
+    len: usize,
+}
+
+impl Errors {
+    pub fn new() -> Self {
+        Self {
+            error: None,
+            len: 0,
+        }
+    }
+
+    /// Records an error, which may already combine multiple messages.
+    pub fn push(&mut self, error: Error) {
+        for error in error {
+            if error.to_string().starts_with(CAUSED_BY_EARLIER_ERROR) {
+                continue;
+            }
+
+            self.len += 1;
+
+            if self.len > MAX_ERRORS {
+                continue;
+            }
+
+            match &mut self.error {
+                Some(output) => output.combine(error),
+                None => self.error = Some(error),
+            }
+        }
+    }
+
+    /// Fails with every recorded error, if there are any.
+    pub fn finish(self) -> syn::Result<()> {
+        let Some(mut error) = self.error else {
+            return Ok(());
+        };
+
+        if self.len > MAX_ERRORS {
+            error.combine(Error::new(
+                Span::call_site(),
+                format!("{} more errors were not reported", self.len - MAX_ERRORS),
+            ));
+        }
+
+        Err(error)
+    }
+}
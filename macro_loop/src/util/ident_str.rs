@@ -1,6 +1,6 @@
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::ToTokens;
-use syn::{Ident, parse::Parse};
+use syn::{Error, Ident, ext::IdentExt, parse::Parse};
 
 use super::*;
 
@@ -8,14 +8,38 @@ use super::*;
 pub struct IdentStr {
     str: Box<str>,
     span: Span,
+    /// Whether a keyword is emitted as a raw identifier,
+    /// which is the case for idents written raw and for idents made by the macro.
+    raw: bool,
 }
 
 impl IdentStr {
-    pub fn new(str: impl Into<Box<str>>, span: Span) -> Self {
-        Self {
-            str: str.into(),
-            span,
+    /// Makes an ident from computed text, like a concat or a string method's output.
+    ///
+    /// If the text is a keyword it becomes a raw identifier (`r#type`),
+    /// so that computing a name never turns it into syntax.
+    pub fn new(str: impl Into<Box<str>>, span: Span) -> syn::Result<Self> {
+        let str = str.into();
+
+        if !is_valid_ident(&str) {
+            return Err(Error::new(
+                span,
+                format!("`{str}` is not a valid identifier"),
+            ));
+        }
+
+        if is_path_keyword(&str) {
+            return Err(Error::new(
+                span,
+                format!("`{str}` is a keyword which can't be a raw identifier"),
+            ));
         }
+
+        Ok(Self {
+            str,
+            span,
+            raw: true,
+        })
     }
 
     pub fn str(&self) -> &str {
@@ -29,18 +53,26 @@ impl IdentStr {
 
 impl Parse for IdentStr {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let ident = input.parse::<Ident>()?;
+        let ident = Ident::parse_any(input)?;
 
+        // raw identifiers are stored without `r#`, which is added back when emitted
         Ok(Self {
-            str: ident.to_string().into_boxed_str(),
+            str: ident.unraw().to_string().into_boxed_str(),
             span: ident.span(),
+            raw: ident.to_string().starts_with("r#"),
         })
     }
 }
 
 impl ToTokens for IdentStr {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        Ident::new(&self.str, self.span).to_tokens(tokens);
+        // keywords are only raw if they were written raw or made by the macro,
+        // so a written `const` or `true` stays a keyword
+        if self.raw && is_keyword(&self.str) {
+            Ident::new_raw(&self.str, self.span).to_tokens(tokens);
+        } else {
+            Ident::new(&self.str, self.span).to_tokens(tokens);
+        }
     }
 }
 
@@ -49,3 +81,49 @@ impl Spanned for IdentStr {
         self.span
     }
 }
+
+/// Keywords and reserved words of every edition, which are emitted as raw identifiers.
+///
+/// A proc macro can't see the edition of the code it expands in,
+/// so words which are only reserved in newer editions (`async`, `try`, `gen`) are included too.
+/// This is harmless because raw identifiers are valid in every edition.
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "gen",
+];
+
+pub fn is_keyword(str: &str) -> bool {
+    KEYWORDS.contains(&str)
+}
+
+/// Keywords that start paths, which cannot be raw identifiers.
+pub fn is_path_keyword(str: &str) -> bool {
+    matches!(str, "self" | "Self" | "super" | "crate")
+}
+
+/// Whether `str` can be emitted as an identifier, which is a raw identifier for keywords.
+pub fn is_valid_ident(str: &str) -> bool {
+    let mut chars = str.chars();
+    let is_ascii_ident = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if is_ascii_ident || str.is_ascii() {
+        return is_ascii_ident;
+    }
+
+    // non-ascii identifiers are checked by the tokenizer
+    let mut tokens = match str.parse::<TokenStream>() {
+        Ok(tokens) => tokens.into_iter(),
+        Err(_) => return false,
+    };
+
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), None) => ident == str,
+        _ => false,
+    }
+}
@@ -1,6 +1,20 @@
+mod case;
+mod diagnostic;
+mod dump;
+mod errors;
 mod ident_str;
+mod note;
+mod pretty;
 mod spanned;
+mod suggest;
 mod to_tokens_spanned;
+pub use case::*;
+pub use diagnostic::*;
+pub use dump::*;
+pub use errors::*;
 pub use ident_str::*;
+pub use note::*;
+pub use pretty::*;
 pub use spanned::*;
+pub use suggest::*;
 pub use to_tokens_spanned::*;
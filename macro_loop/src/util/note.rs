@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+use proc_macro2::TokenStream;
+use syn::Error;
+
+/// Adds a note line to every message of an error, keeping their spans.
+///
+/// Notes are added as an error propagates out of nested fragments,
+/// so they read from the innermost fragment to the outermost one.
+pub fn with_note(error: Error, note: impl Display) -> Error {
+    (&error)
+        .into_iter()
+        .map(|error| Error::new_spanned(span_range(&error), format!("{error}\nnote: {note}")))
+        .reduce(|mut output, error| {
+            output.combine(error);
+            output
+        })
+        .unwrap_or(error)
+}
+
+/// Tokens that start and end where an error does.
+///
+/// `Error::span` can only return the start of the range on stable,
+/// but the first and last tokens of the compile error keep both ends of it.
+fn span_range(error: &Error) -> TokenStream {
+    let mut tokens = error.to_compile_error().into_iter();
+    let first = tokens.next();
+    let last = tokens.last();
+
+    first.into_iter().chain(last).collect()
+}
@@ -0,0 +1,312 @@
+use proc_macro2::{Delimiter, Group, Punct, Spacing, TokenStream, TokenTree};
+
+/// Keywords which are followed by a space, even before a group like `if (a, b) == c`.
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "dyn", "else", "for", "if", "impl", "in", "let", "loop", "match",
+    "move", "mut", "ref", "return", "static", "unsafe", "where", "while",
+];
+
+/// Keywords which are followed by a name that can have generics, like `fn name<T>`.
+const ITEM_KEYWORDS: &[&str] = &["enum", "fn", "struct", "trait", "type", "union"];
+
+/// Multi-char operators, which are printed as a whole.
+const OPERATORS: &[&str] = &[
+    "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "%=", "^=", "&=",
+    "|=", "<<", ">>", "<<=", ">>=", "..", "...", "..=",
+];
+
+/// Formats tokens as readable Rust code.
+///
+/// This is a lot simpler than rustfmt, and only decides where lines break and where spaces go.
+/// Items and statements go on their own lines, and blocks are indented.
+pub fn pretty_print(tokens: TokenStream) -> String {
+    let mut printer = Printer::default();
+    printer.print_stream(tokens, Context::Items);
+    printer.output.push('\n');
+
+    printer.output
+}
+
+#[derive(Default)]
+struct Printer {
+    output: String,
+    indent: usize,
+    /// How many line breaks go before the next token.
+    line_breaks: usize,
+    prev: Prev,
+    /// The last two tokens, most recent first.
+    history: [String; 2],
+    /// The puncts of an operator that isn't printed yet, like `-` before the `>` of `->`.
+    op: String,
+    /// How many generics `<` are open.
+    generics: usize,
+    /// Whether a `|` opened the params of a closure.
+    closure_params: bool,
+}
+
+/// Where tokens are, which decides which separators break lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    /// The top level, where items are separated by an empty line.
+    Items,
+    /// A block, whose items, statements, fields or match arms go on their own lines.
+    Block,
+    /// Parentheses, brackets or a `use` tree, which don't break lines.
+    Inline,
+}
+
+/// What the previous token was, which decides the spacing before the next one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Prev {
+    /// Nothing, at the start of a group.
+    #[default]
+    Start,
+    /// A value, like an ident, a literal or a group, which can be followed by a binary operator.
+    Value,
+    /// A keyword or an operator, which is followed by a value.
+    Operator,
+    /// A token which sticks to the next one, like `.` or the `&` of `&x`.
+    Glue,
+}
+
+impl Printer {
+    fn print_stream(&mut self, tokens: TokenStream, context: Context) {
+        for token in tokens {
+            // an operator continues until a token that isn't a punct
+            if !matches!(token, TokenTree::Punct(_)) {
+                self.flush_op(context);
+            }
+
+            match token {
+                TokenTree::Punct(punct) => self.print_punct(&punct, context),
+                TokenTree::Group(group) => self.print_group(&group, context),
+                TokenTree::Ident(ident) => self.print_word(&ident.to_string()),
+                TokenTree::Literal(lit) => self.print_word(&lit.to_string()),
+            }
+        }
+
+        self.flush_op(context);
+    }
+
+    fn print_word(&mut self, word: &str) {
+        if word == "else" {
+            self.attach();
+        }
+
+        self.write(word, true);
+
+        self.prev = if KEYWORDS.contains(&word) {
+            Prev::Operator
+        } else {
+            Prev::Value
+        };
+    }
+
+    fn print_group(&mut self, group: &Group, context: Context) {
+        let (open, close) = match group.delimiter() {
+            Delimiter::Parenthesis => ("(", ")"),
+            Delimiter::Bracket => ("[", "]"),
+            // `use a::{b, c}`
+            Delimiter::Brace if self.prev == Prev::Glue => ("{", "}"),
+            Delimiter::Brace => return self.print_block(group, context),
+            Delimiter::None => return self.print_stream(group.stream(), context),
+        };
+
+        let attribute = group.delimiter() == Delimiter::Bracket
+            && (self.history[0] == "#" || self.history == ["!", "#"]);
+
+        // `f(x)` and `a[0]`, but `a + (b)`
+        self.write(open, self.prev != Prev::Value);
+        self.prev = Prev::Start;
+
+        self.print_inner(group.stream(), Context::Inline);
+
+        self.attach();
+        self.write(close, false);
+        self.prev = Prev::Value;
+
+        if attribute && context != Context::Inline {
+            self.line_breaks = 1;
+        }
+    }
+
+    fn print_block(&mut self, group: &Group, context: Context) {
+        if group.stream().is_empty() {
+            self.write("{}", true);
+        } else {
+            self.write("{", true);
+            self.indent += 1;
+            self.line_breaks = 1;
+            self.prev = Prev::Start;
+
+            self.print_inner(group.stream(), Context::Block);
+
+            self.indent -= 1;
+            self.line_breaks = 1;
+            self.write("}", false);
+        }
+
+        self.prev = Prev::Value;
+
+        self.line_breaks = line_breaks_after_item(context);
+    }
+
+    /// Prints the stream of a group, whose generics and closures can't leak out of it.
+    fn print_inner(&mut self, tokens: TokenStream, context: Context) {
+        let generics = std::mem::take(&mut self.generics);
+        let closure_params = std::mem::take(&mut self.closure_params);
+
+        self.print_stream(tokens, context);
+
+        self.generics = generics;
+        self.closure_params = closure_params;
+    }
+
+    fn print_punct(&mut self, punct: &Punct, context: Context) {
+        let ch = punct.as_char();
+
+        if ch == '\'' {
+            self.flush_op(context);
+            self.write("'", true);
+            self.prev = Prev::Glue;
+
+            return;
+        }
+
+        let longer_op = format!("{}{ch}", self.op);
+        if !self.op.is_empty() && !OPERATORS.iter().any(|op| op.starts_with(&longer_op)) {
+            self.flush_op(context);
+        }
+
+        self.op.push(ch);
+
+        if punct.spacing() == Spacing::Alone {
+            self.flush_op(context);
+        }
+    }
+
+    fn flush_op(&mut self, context: Context) {
+        if self.op.is_empty() {
+            return;
+        }
+
+        let op = std::mem::take(&mut self.op);
+        // a new statement doesn't continue the previous one, like `<T as Trait>::f()` after a block
+        let after_value = self.prev == Prev::Value && self.line_breaks == 0;
+
+        match op.as_str() {
+            "," => {
+                self.attach();
+                self.write(",", false);
+                self.prev = Prev::Operator;
+
+                if context == Context::Block {
+                    self.line_breaks = 1;
+                }
+            }
+            ";" => {
+                self.attach();
+                self.write(";", false);
+                self.prev = Prev::Operator;
+
+                self.line_breaks = line_breaks_after_item(context);
+            }
+            ":" | "?" => {
+                self.attach();
+                self.write(&op, false);
+                self.prev = if op == "?" {
+                    Prev::Value
+                } else {
+                    Prev::Operator
+                };
+            }
+
+            // `a.b`, `a::b` and `0..4`, but `= ::std` and `= ..4`
+            "." | "::" | ".." | "..=" | "..." => {
+                self.attach();
+                self.write(&op, self.prev == Prev::Operator);
+                self.prev = Prev::Glue;
+            }
+
+            // `#[attr]`, `macro!` and `!a`
+            "#" | "!" => {
+                self.write(&op, !after_value);
+                self.prev = Prev::Glue;
+            }
+
+            "<" if !after_value || self.names_generic_item() => {
+                let space =
+                    self.prev == Prev::Operator && !["impl", "for"].contains(&&*self.history[0]);
+
+                self.write("<", space);
+                self.generics += 1;
+                self.prev = Prev::Glue;
+            }
+            ">" | ">>" if self.generics > 0 => {
+                self.write(&op, false);
+                self.generics = self.generics.saturating_sub(op.len());
+                self.prev = Prev::Value;
+            }
+
+            "|" if self.closure_params => {
+                self.write("|", false);
+                self.closure_params = false;
+                self.prev = Prev::Operator;
+            }
+            "|" if !after_value => {
+                self.write("|", true);
+                self.closure_params = true;
+                self.prev = Prev::Glue;
+            }
+
+            // `&x`, `*x` and `-1`
+            "&" | "&&" | "*" | "-" if !after_value => {
+                self.write(&op, true);
+                self.prev = Prev::Glue;
+            }
+
+            _ => {
+                self.write(&op, true);
+                self.prev = Prev::Operator;
+            }
+        }
+    }
+
+    /// Whether the previous ident is a type or an item name, like `Vec` or the `f` of `fn f`,
+    /// so a `<` after it opens generics instead of comparing.
+    fn names_generic_item(&self) -> bool {
+        self.history[0].starts_with(char::is_uppercase)
+            || ITEM_KEYWORDS.contains(&&*self.history[1])
+    }
+
+    /// Cancels the line break after a block, for `},`, `};` and `} else`.
+    fn attach(&mut self) {
+        if self.output.ends_with('}') {
+            self.line_breaks = 0;
+        }
+    }
+
+    fn write(&mut self, text: &str, space: bool) {
+        if self.line_breaks > 0 && !self.output.is_empty() {
+            self.output.push_str(&"\n".repeat(self.line_breaks));
+            self.output.push_str(&"    ".repeat(self.indent));
+        } else if space && !matches!(self.prev, Prev::Start | Prev::Glue) {
+            self.output.push(' ');
+        }
+
+        self.line_breaks = 0;
+        self.output.push_str(text);
+
+        let prev = std::mem::replace(&mut self.history[0], text.to_string());
+        self.history[1] = prev;
+    }
+}
+
+/// How many line breaks go after an item or a statement.
+fn line_breaks_after_item(context: Context) -> usize {
+    match context {
+        Context::Items => 2,
+        Context::Block => 1,
+        Context::Inline => 0,
+    }
+}
@@ -0,0 +1,63 @@
+/// The candidate closest to `target`, if one is close enough to be a likely typo.
+///
+/// Like rustc, a candidate may be up to a third of `target`'s length away,
+/// and a candidate that only differs in case is always close enough.
+pub fn suggest<'a>(target: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (target.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != target)
+        .filter_map(|candidate| {
+            let distance = if candidate.eq_ignore_ascii_case(target) {
+                0
+            } else {
+                edit_distance(target, candidate)
+            };
+
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Formats a suggestion as the end of an error message.
+pub fn did_you_mean(suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(". did you mean `{suggestion}`?"),
+        None => String::new(),
+    }
+}
+
+/// The edit distance between two strings, counted in chars,
+/// where swapping two adjacent chars is a single edit (`itmes` is 1 away from `items`).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // `distances[i][j]` is the distance between `a[..i]` and `b[..j]`
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
@@ -0,0 +1,137 @@
+use std::{collections::HashSet, rc::Rc};
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{Error, LitStr};
+
+use super::*;
+
+/// A closure value, which keeps the names it uses from where it is written.
+pub struct Closure {
+    expr: Rc<ExprClosure>,
+    captures: Captures,
+}
+
+impl Closure {
+    /// Creates a closure, capturing the bindings of `namespace` that its body mentions.
+    pub fn new(expr: Rc<ExprClosure>, namespace: &Namespace) -> Self {
+        let mut words = Words::default();
+        words.collect(expr.to_token_stream());
+
+        let params = expr
+            .inputs
+            .iter()
+            .flat_map(Pattern::names)
+            .map(|name| name.as_str())
+            .collect::<HashSet<_>>();
+
+        let reads = |name: &str| !params.contains(name) && words.mentions(name);
+
+        // the bindings that the parameters shadow are captured too,
+        // so binding the parameters warns about shadowing them
+        let captures = namespace.capture(|name| params.contains(name) || reads(name));
+        captures.use_sites(reads);
+
+        Self { expr, captures }
+    }
+
+    /// Evaluates the body with the inputs bound to the closure's patterns,
+    /// in a namespace of the names the closure captured where it was written.
+    pub fn call<'v>(&self, inputs: &[ValueRef<'v>]) -> syn::Result<ValueRef<'v>> {
+        if inputs.len() != self.expr.inputs.len() {
+            return Err(Error::new_spanned(
+                self,
+                format!("expected a closure with {} parameter(s)", inputs.len()),
+            ));
+        }
+
+        let captured = Namespace::with_captures(&self.captures);
+
+        let mut namespace = captured.fork();
+        for (pat, input) in self.expr.inputs.iter().zip(inputs) {
+            namespace.insert_pat(pat, input.clone())?;
+        }
+
+        let output = Value::from_expr(&self.expr.body, &namespace)?;
+
+        Ok(ValueRef::Owned(output.to_static()))
+    }
+}
+
+impl ToTokens for Closure {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.expr.to_tokens(tokens);
+    }
+}
+
+/// The names that a closure reads, which decide the names it captures.
+///
+/// These are the names of `@name` fragments, and the names of placeholders in strings
+/// (`{@name}` for interpolation, and `{name}` and `{:name$}` for `format`),
+/// which look names up by their text.
+#[derive(Default)]
+struct Words {
+    names: HashSet<String>,
+}
+
+impl Words {
+    fn collect(&mut self, tokens: TokenStream) {
+        let mut after_at = false;
+
+        for token in tokens {
+            match &token {
+                TokenTree::Group(group) => self.collect(group.stream()),
+                TokenTree::Ident(ident) if after_at => _ = self.names.insert(ident.to_string()),
+                TokenTree::Literal(_) => {
+                    if let Ok(lit) = syn::parse2::<LitStr>(token.clone().into()) {
+                        self.collect_placeholders(&lit.value());
+                    }
+                }
+                _ => {}
+            }
+
+            after_at = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '@');
+        }
+    }
+
+    fn collect_placeholders(&mut self, str: &str) {
+        for placeholder in str.split('{').skip(1) {
+            // `{{` is an escaped brace, which splits into an empty part
+            if placeholder.is_empty() {
+                continue;
+            }
+
+            let placeholder = placeholder.split('}').next().unwrap_or_default();
+
+            let (arg, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+            if !arg.contains('@') {
+                self.names.insert(arg.trim().to_string());
+            }
+
+            // `{@x + @y}` reads every `@name`, and `{:w$}` reads `w`
+            for part in placeholder.split('@').skip(1) {
+                self.names.insert(leading_name(part).to_string());
+            }
+            for part in spec.split('$').rev().skip(1) {
+                let start = part
+                    .rfind(|c: char| !is_name_char(c))
+                    .map_or(0, |idx| idx + 1);
+                self.names.insert(part[start..].to_string());
+            }
+        }
+    }
+
+    fn mentions(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+}
+
+fn leading_name(str: &str) -> &str {
+    let end = str.find(|c: char| !is_name_char(c)).unwrap_or(str.len());
+
+    &str[..end]
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
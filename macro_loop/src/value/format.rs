@@ -0,0 +1,442 @@
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::Error;
+
+use super::*;
+
+/// Formats a template like Rust's `format!`,
+/// where positional placeholders (`{}`, `{0}`) refer to `inputs`
+/// and named placeholders (`{N}`) are captured from the namespace.
+pub fn format(
+    template: &str,
+    inputs: &[ValueRef],
+    namespace: &Namespace,
+    span: Span,
+) -> syn::Result<String> {
+    let mut args = Args {
+        inputs,
+        namespace,
+        span,
+        used: vec![false; inputs.len()],
+        next: 0,
+    };
+
+    let mut output = String::new();
+
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(Error::new(
+                                span,
+                                "unterminated `{` in format string. use `{{` to emit `{`",
+                            ));
+                        }
+                    }
+                }
+
+                let (arg, spec) = placeholder.split_once(':').unwrap_or((&placeholder, ""));
+                let spec = FormatSpec::parse(spec, span)?;
+
+                let value = match arg.trim() {
+                    "" => args.next()?,
+                    arg => args.get(arg)?,
+                };
+
+                let width = spec
+                    .width
+                    .as_ref()
+                    .map(|width| args.count(width))
+                    .transpose()?;
+                let precision = spec
+                    .precision
+                    .as_ref()
+                    .map(|precision| args.count(precision))
+                    .transpose()?;
+
+                output += &spec.format(value, width, precision)?;
+            }
+
+            '}' => {
+                return Err(Error::new(
+                    span,
+                    "unmatched `}` in format string. use `}}` to emit `}`",
+                ));
+            }
+
+            c => output.push(c),
+        }
+    }
+
+    if let Some(idx) = args.used.iter().position(|used| !used) {
+        return Err(Error::new_spanned(&inputs[idx], "argument never used"));
+    }
+
+    Ok(output)
+}
+
+struct Args<'a> {
+    inputs: &'a [ValueRef<'a>],
+    namespace: &'a Namespace<'a, 'a>,
+    span: Span,
+    used: Vec<bool>,
+    next: usize,
+}
+
+impl<'a> Args<'a> {
+    fn next(&mut self) -> syn::Result<&'a Value<'a>> {
+        self.next += 1;
+
+        self.positional(self.next - 1)
+    }
+
+    fn get(&mut self, arg: &str) -> syn::Result<&'a Value<'a>> {
+        match arg.parse::<usize>() {
+            Ok(idx) => self.positional(idx),
+            Err(_) => self.namespace.get(&Name::new(arg, self.span)),
+        }
+    }
+
+    fn positional(&mut self, idx: usize) -> syn::Result<&'a Value<'a>> {
+        match self.inputs.get(idx) {
+            Some(input) => {
+                self.used[idx] = true;
+
+                Ok(input)
+            }
+
+            None => Err(Error::new(
+                self.span,
+                format!(
+                    "invalid reference to positional argument {idx}. there are {} arguments",
+                    self.inputs.len()
+                ),
+            )),
+        }
+    }
+
+    fn count(&mut self, count: &Count) -> syn::Result<usize> {
+        let count = match count {
+            Count::Is(count) => *count,
+
+            Count::Arg(arg) => match self.get(arg)? {
+                Value::Int(value) => value.base10_parse()?,
+                value => {
+                    return Err(Error::new_spanned(
+                        value,
+                        value.expected("a width or precision int"),
+                    ));
+                }
+            },
+        };
+
+        checked_len(Some(count), self.span)
+    }
+}
+
+/// The part of a placeholder after `:`, which is `[[fill]align][+][#][0][width][.precision][type]`.
+struct FormatSpec {
+    fill: char,
+    align: Option<Align>,
+    plus: bool,
+    alternate: bool,
+    zero: bool,
+    width: Option<Count>,
+    precision: Option<Count>,
+    kind: FormatKind,
+}
+
+#[derive(Clone, Copy)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+enum Count {
+    Is(usize),
+    Arg(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FormatKind {
+    Display,
+    Debug,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+    LowerExp,
+    UpperExp,
+}
+
+impl FormatSpec {
+    fn parse(spec: &str, span: Span) -> syn::Result<Self> {
+        let chars = spec.chars().collect::<Vec<_>>();
+        let mut i = 0;
+
+        let align = |c: char| match c {
+            '<' => Some(Align::Left),
+            '^' => Some(Align::Center),
+            '>' => Some(Align::Right),
+            _ => None,
+        };
+
+        let (fill, align) = match (chars.first(), chars.get(1)) {
+            (Some(&fill), Some(&c)) if align(c).is_some() => {
+                i = 2;
+                (fill, align(c))
+            }
+            (Some(&c), _) if align(c).is_some() => {
+                i = 1;
+                (' ', align(c))
+            }
+            _ => (' ', None),
+        };
+
+        let plus = parse_flag(&chars, &mut i, '+');
+        let _minus = parse_flag(&chars, &mut i, '-');
+        let alternate = parse_flag(&chars, &mut i, '#');
+        // `0$` is a width argument rather than the `0` flag
+        let zero = chars.get(i + 1) != Some(&'$') && parse_flag(&chars, &mut i, '0');
+
+        let width = parse_count(&chars, &mut i);
+
+        let precision = if chars.get(i) == Some(&'.') {
+            i += 1;
+
+            match parse_count(&chars, &mut i) {
+                Some(precision) => Some(precision),
+                None => return Err(Error::new(span, "expected a precision after `.`")),
+            }
+        } else {
+            None
+        };
+
+        let kind = match chars[i..].iter().collect::<String>().as_str() {
+            "" => FormatKind::Display,
+            "?" => FormatKind::Debug,
+            "x" | "x?" => FormatKind::LowerHex,
+            "X" | "X?" => FormatKind::UpperHex,
+            "o" => FormatKind::Octal,
+            "b" => FormatKind::Binary,
+            "e" => FormatKind::LowerExp,
+            "E" => FormatKind::UpperExp,
+
+            kind => {
+                return Err(Error::new(span, format!("unknown format trait `{kind}`")));
+            }
+        };
+
+        Ok(Self {
+            fill,
+            align,
+            plus,
+            alternate,
+            zero,
+            width,
+            precision,
+            kind,
+        })
+    }
+
+    fn format(
+        &self,
+        value: &Value,
+        width: Option<usize>,
+        precision: Option<usize>,
+    ) -> syn::Result<String> {
+        let (sign, prefix, body, is_number) = match (value, self.kind) {
+            (Value::Int(int), _) => {
+                let int = int.base10_parse::<i128>()?;
+                // negative ints print their magnitude in every radix, not their two's complement
+                let sign = if int < 0 {
+                    "-"
+                } else if self.plus {
+                    "+"
+                } else {
+                    ""
+                };
+
+                let abs = int.unsigned_abs();
+                let (prefix, body) = match self.kind {
+                    FormatKind::Display | FormatKind::Debug => ("", abs.to_string()),
+                    FormatKind::LowerHex => ("0x", format!("{abs:x}")),
+                    FormatKind::UpperHex => ("0x", format!("{abs:X}")),
+                    FormatKind::Octal => ("0o", format!("{abs:o}")),
+                    FormatKind::Binary => ("0b", format!("{abs:b}")),
+                    FormatKind::LowerExp => ("", format!("{abs:e}")),
+                    FormatKind::UpperExp => ("", format!("{abs:E}")),
+                };
+
+                (sign, if self.alternate { prefix } else { "" }, body, true)
+            }
+
+            (
+                Value::Float(float),
+                FormatKind::Display
+                | FormatKind::Debug
+                | FormatKind::LowerExp
+                | FormatKind::UpperExp,
+            ) => {
+                let float = float.base10_parse::<f64>()?;
+                let sign = if float.is_sign_negative() {
+                    "-"
+                } else if self.plus {
+                    "+"
+                } else {
+                    ""
+                };
+
+                let abs = float.abs();
+                let body = match (self.kind, precision) {
+                    (FormatKind::LowerExp, None) => format!("{abs:e}"),
+                    (FormatKind::UpperExp, None) => format!("{abs:E}"),
+                    (FormatKind::LowerExp, Some(precision)) => format!("{abs:.precision$e}"),
+                    (FormatKind::UpperExp, Some(precision)) => format!("{abs:.precision$E}"),
+                    (FormatKind::Debug, None) => format!("{abs:?}"),
+                    (_, None) => format!("{abs}"),
+                    (_, Some(precision)) => format!("{abs:.precision$}"),
+                };
+
+                (sign, "", body, true)
+            }
+
+            (_, FormatKind::Display | FormatKind::Debug) => {
+                let mut body = self.text(value)?;
+                if let Some(precision) = precision {
+                    body = body.chars().take(precision).collect();
+                }
+
+                ("", "", body, false)
+            }
+
+            _ => {
+                return Err(Error::new_spanned(
+                    value,
+                    format!(
+                        "{} cannot be formatted with this format trait",
+                        value.describe()
+                    ),
+                ));
+            }
+        };
+
+        let len = sign.chars().count() + prefix.chars().count() + body.chars().count();
+        let padding = width.unwrap_or(0).saturating_sub(len);
+
+        if self.zero && is_number {
+            return Ok(format!("{sign}{prefix}{}{body}", "0".repeat(padding)));
+        }
+
+        let align = match self.align {
+            Some(align) => align,
+            None if is_number => Align::Right,
+            None => Align::Left,
+        };
+
+        let (left, right) = match align {
+            Align::Left => (0, padding),
+            Align::Center => (padding / 2, padding - padding / 2),
+            Align::Right => (padding, 0),
+        };
+
+        let fill = |count: usize| self.fill.to_string().repeat(count);
+
+        Ok(format!("{}{sign}{prefix}{body}{}", fill(left), fill(right)))
+    }
+
+    /// The `Display` or `Debug` text of a non-number value.
+    fn text(&self, value: &Value) -> syn::Result<String> {
+        let is_debug = self.kind == FormatKind::Debug;
+
+        Ok(match value {
+            Value::Bool(value) => value.value.to_string(),
+            Value::Ident(value) => value.str().to_string(),
+
+            Value::Str(value) if is_debug => format!("{:?}", value.value()),
+            Value::Str(value) => value.value(),
+            Value::Char(value) if is_debug => format!("{:?}", value.value()),
+            Value::Char(value) => value.value().to_string(),
+
+            Value::CStr(_) | Value::ByteStr(_) if is_debug => value.to_token_stream().to_string(),
+
+            Value::List(list) if is_debug => format!(
+                "[{}]",
+                list.items
+                    .iter()
+                    .map(|item| self.text(item))
+                    .collect::<syn::Result<Vec<_>>>()?
+                    .join(", ")
+            ),
+
+            Value::Int(_) | Value::Float(_) => {
+                return self.format(value, None, None);
+            }
+
+            _ => {
+                return Err(Error::new_spanned(
+                    value,
+                    format!(
+                        "{} cannot be formatted with `{{}}`. try `{{:?}}`",
+                        value.describe()
+                    ),
+                ));
+            }
+        })
+    }
+}
+
+fn parse_flag(chars: &[char], i: &mut usize, flag: char) -> bool {
+    let is_flag = chars.get(*i) == Some(&flag);
+    if is_flag {
+        *i += 1;
+    }
+
+    is_flag
+}
+
+/// Parses a width or precision, which is either an int or an argument followed by `$`.
+fn parse_count(chars: &[char], i: &mut usize) -> Option<Count> {
+    let start = *i;
+    let mut end = start;
+    while chars
+        .get(end)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+    {
+        end += 1;
+    }
+
+    let run = chars[start..end].iter().collect::<String>();
+
+    if chars.get(end) == Some(&'$') && !run.is_empty() {
+        *i = end + 1;
+
+        return Some(Count::Arg(run));
+    }
+
+    let digits = run.chars().take_while(char::is_ascii_digit).count();
+    match run[..digits].parse() {
+        Ok(count) => {
+            *i = start + digits;
+
+            Some(Count::Is(count))
+        }
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,87 @@
+use syn::Error;
+
+use super::{format::format, methods::list, *};
+
+const FUNCTIONS: &[&str] = &["product", "format"];
+
+impl<'v> ValueRef<'v> {
+    pub fn call(
+        func: &IdentStr,
+        inputs: &[ValueRef<'v>],
+        namespace: &Namespace<'v, 'v>,
+    ) -> syn::Result<ValueRef<'v>> {
+        Ok(match func.str() {
+            "product" => Self::product_fn(func, inputs)?,
+            "format" => Self::format_fn(func, inputs, namespace)?,
+
+            name => {
+                let suggestion = suggest(name, FUNCTIONS.iter().copied());
+
+                return Err(Error::new_spanned(
+                    func,
+                    format!("unknown function `{name}`{}", did_you_mean(suggestion)),
+                ));
+            }
+        })
+    }
+
+    /// `format(template, args...)`, which formats a string like Rust's `format!`.
+    fn format_fn(
+        func: &IdentStr,
+        inputs: &[ValueRef<'v>],
+        namespace: &Namespace<'v, 'v>,
+    ) -> syn::Result<Self> {
+        let (template, inputs) = match inputs.split_first() {
+            Some((template, inputs)) => match &**template {
+                Value::Str(template) => (template, inputs),
+                _ => {
+                    return Err(Error::new_spanned(
+                        template,
+                        template.expected("a format string"),
+                    ));
+                }
+            },
+
+            None => return Err(Error::new_spanned(func, "expected a format string")),
+        };
+
+        let output = format(&template.value(), inputs, namespace, template.span())?;
+
+        Ok(Self::Owned(string(output, func.span())))
+    }
+
+    /// The cartesian product of lists, in the order of nested `@for` loops:
+    /// `product([a, b], [x, y])` is `[[a, x], [a, y], [b, x], [b, y]]`.
+    fn product_fn(func: &IdentStr, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let span = func.span();
+
+        let inputs = inputs
+            .iter()
+            .map(|input| input.clone().into_items(span))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let count = inputs
+            .iter()
+            .try_fold(1usize, |count, items| count.checked_mul(items.len()));
+        checked_len(count, span)?;
+
+        let mut output = vec![Vec::new()];
+        for items in inputs {
+            output = output
+                .into_iter()
+                .flat_map(|prefix| {
+                    items.iter().map(move |item| {
+                        let mut tuple = prefix.clone();
+                        tuple.push(item.clone());
+                        tuple
+                    })
+                })
+                .collect();
+        }
+
+        Ok(list(
+            output.into_iter().map(|tuple| list(tuple, span)).collect(),
+            span,
+        ))
+    }
+}
@@ -1,68 +1,248 @@
+use std::{iter::once, mem::discriminant, slice::from_ref};
+
 use proc_macro2::Span;
 use syn::{Error, LitInt, Token};
 
-use super::*;
+use super::{format::format, *};
+
+const VALUE_METHODS: &[&str] = &["min", "max", "clamp"];
+
+const LIST_METHODS: &[&str] = &[
+    "enumerate",
+    "index",
+    "slice",
+    "slice_inclusive",
+    "step_by",
+    "len",
+    "is_empty",
+    "reverse",
+    "contains",
+    "position",
+    "first",
+    "last",
+    "skip",
+    "take",
+    "flatten",
+    "concat",
+    "repeat",
+    "combinations",
+    "combinations_with_replacement",
+    "permutations",
+    "powerset",
+    "zip",
+    "unzip",
+    "windows",
+    "chunks",
+    "transpose",
+    "interleave",
+    "rotate",
+    "sum",
+    "product",
+    "running_sum",
+    "all",
+    "any",
+    "count",
+    "sort",
+    "sort_desc",
+    "dedup",
+    "unique",
+    "union",
+    "intersection",
+    "difference",
+    "is_subset",
+    "map",
+    "filter",
+    "filter_map",
+    "flat_map",
+    "fold",
+    "any_by",
+    "all_by",
+    "sort_by_key",
+];
+
+const TEXT_METHODS: &[&str] = &[
+    "index",
+    "slice",
+    "slice_inclusive",
+    "len",
+    "is_empty",
+    "contains",
+    "starts_with",
+    "ends_with",
+    "find",
+    "replace",
+    "split",
+    "strip_prefix",
+    "strip_suffix",
+    "format",
+    "is_keyword",
+    "is_valid_ident",
+    "chars",
+    "char_at",
+    "trim",
+    "to_upper",
+    "to_lower",
+    "to_snake_case",
+    "to_camel_case",
+    "to_pascal_case",
+    "to_screaming_snake_case",
+    "to_kebab_case",
+];
 
 impl<'v> ValueRef<'v> {
-    pub fn method(self, method: &IdentStr, inputs: &[ValueRef<'v>]) -> syn::Result<ValueRef<'v>> {
+    pub fn method(
+        self,
+        method: &IdentStr,
+        inputs: &[ValueRef<'v>],
+        namespace: &Namespace<'v, 'v>,
+    ) -> syn::Result<ValueRef<'v>> {
         Ok(match method.str() {
             "enumerate" => Self::enumerate_method(self, method.span(), inputs)?,
             "index" => Self::index_method(&self, method.span(), inputs)?,
+            "slice" => Self::slice_method(&self, method.span(), inputs, false)?,
+            "slice_inclusive" => Self::slice_method(&self, method.span(), inputs, true)?,
+            "step_by" => Self::step_by_method(self, method.span(), inputs)?,
             "min" => Self::min_method(self, method.span(), inputs)?,
             "max" => Self::max_method(self, method.span(), inputs)?,
             "clamp" => Self::clamp_method(self, method.span(), inputs)?,
 
-            _ => return Err(Error::new_spanned(&method, "Unknown method")),
+            "len" => Self::len_method(&self, method.span(), inputs)?,
+            "is_empty" => Self::is_empty_method(&self, method.span(), inputs)?,
+            "reverse" => Self::reverse_method(self, method.span(), inputs)?,
+            "contains" => Self::contains_method(&self, method.span(), inputs)?,
+            "position" => Self::position_method(&self, method.span(), inputs)?,
+            "first" => Self::first_method(self, method.span(), inputs)?,
+            "last" => Self::last_method(self, method.span(), inputs)?,
+            "skip" => Self::skip_method(self, method.span(), inputs)?,
+            "take" => Self::take_method(self, method.span(), inputs)?,
+            "flatten" => Self::flatten_method(self, method.span(), inputs)?,
+            "concat" => Self::concat_method(self, method.span(), inputs)?,
+            "repeat" => Self::repeat_method(self, method.span(), inputs)?,
+
+            "combinations" => Self::combinations_method(self, method.span(), inputs, false)?,
+            "combinations_with_replacement" => {
+                Self::combinations_method(self, method.span(), inputs, true)?
+            }
+            "permutations" => Self::permutations_method(self, method.span(), inputs)?,
+            "powerset" => Self::powerset_method(self, method.span(), inputs)?,
+
+            "zip" => Self::zip_method(self, method.span(), inputs)?,
+            "unzip" => Self::unzip_method(self, method.span(), inputs)?,
+            "windows" => Self::windows_method(self, method.span(), inputs)?,
+            "chunks" => Self::chunks_method(self, method.span(), inputs)?,
+            "transpose" => Self::transpose_method(self, method.span(), inputs)?,
+            "interleave" => Self::interleave_method(self, method.span(), inputs)?,
+            "rotate" => Self::rotate_method(self, method.span(), inputs)?,
+
+            "sum" => Self::sum_method(&self, method.span(), inputs)?,
+            "product" => Self::product_method(&self, method.span(), inputs)?,
+            "running_sum" => Self::running_sum_method(&self, method.span(), inputs)?,
+            "all" => Self::all_method(&self, method.span(), inputs)?,
+            "any" => Self::any_method(&self, method.span(), inputs)?,
+            "count" => Self::count_method(&self, method.span(), inputs)?,
+
+            "sort" => Self::sort_method(self, method.span(), inputs, false)?,
+            "sort_desc" => Self::sort_method(self, method.span(), inputs, true)?,
+            "dedup" => Self::dedup_method(self, method.span(), inputs)?,
+            "unique" => Self::unique_method(self, method.span(), inputs)?,
+            "union" => Self::union_method(self, method.span(), inputs)?,
+            "intersection" => Self::intersection_method(self, method.span(), inputs, true)?,
+            "difference" => Self::intersection_method(self, method.span(), inputs, false)?,
+            "is_subset" => Self::is_subset_method(&self, method.span(), inputs)?,
+
+            "starts_with" => Self::starts_with_method(&self, method.span(), inputs)?,
+            "ends_with" => Self::ends_with_method(&self, method.span(), inputs)?,
+            "find" => Self::find_method(&self, method.span(), inputs)?,
+            "replace" => Self::replace_method(&self, method.span(), inputs)?,
+            "split" => Self::split_method(&self, method.span(), inputs)?,
+            "strip_prefix" => Self::strip_prefix_method(&self, method.span(), inputs)?,
+            "strip_suffix" => Self::strip_suffix_method(&self, method.span(), inputs)?,
+            "format" => Self::format_method(&self, method.span(), inputs, namespace)?,
+            "is_keyword" => Self::is_keyword_method(&self, method.span(), inputs)?,
+            "is_valid_ident" => Self::is_valid_ident_method(&self, method.span(), inputs)?,
+            "chars" => Self::chars_method(&self, method.span(), inputs)?,
+            "char_at" => Self::char_at_method(&self, method.span(), inputs)?,
+            "trim" => Self::trim_method(&self, method.span(), inputs)?,
+            "to_upper" => Self::to_upper_method(&self, method.span(), inputs)?,
+            "to_lower" => Self::to_lower_method(&self, method.span(), inputs)?,
+
+            "to_snake_case" => Self::case_method(&self, method.span(), inputs, Case::Snake)?,
+            "to_camel_case" => Self::case_method(&self, method.span(), inputs, Case::Camel)?,
+            "to_pascal_case" => Self::case_method(&self, method.span(), inputs, Case::Pascal)?,
+            "to_screaming_snake_case" => {
+                Self::case_method(&self, method.span(), inputs, Case::ScreamingSnake)?
+            }
+            "to_kebab_case" => Self::case_method(&self, method.span(), inputs, Case::Kebab)?,
+
+            "map" => Self::map_method(self, method.span(), inputs)?,
+            "filter" => Self::filter_method(self, method.span(), inputs)?,
+            "filter_map" => Self::filter_map_method(self, method.span(), inputs)?,
+            "flat_map" => Self::flat_map_method(self, method.span(), inputs)?,
+            "fold" => Self::fold_method(self, method.span(), inputs)?,
+            "any_by" => Self::any_by_method(&self, method.span(), inputs)?,
+            "all_by" => Self::all_by_method(&self, method.span(), inputs)?,
+            "sort_by_key" => Self::sort_by_key_method(self, method.span(), inputs)?,
+
+            _ => {
+                let suggestion = suggest(method.str(), self.method_names());
+
+                return Err(Error::new_spanned(
+                    method,
+                    format!(
+                        "unknown method `{}`{}",
+                        method.str(),
+                        did_you_mean(suggestion)
+                    ),
+                ));
+            }
         })
     }
 
-    fn min_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
-        let &[ref other] = match inputs.try_into() {
-            Ok(inputs) => inputs,
-            _ => return Err(Error::new(span, "expected 1 argument")),
+    /// The methods that apply to this kind of value.
+    fn method_names(&self) -> Vec<&'static str> {
+        let kind_methods = match &**self {
+            Value::List(_) => LIST_METHODS,
+            Value::Str(_) | Value::Ident(_) => TEXT_METHODS,
+            _ => &[],
         };
 
-        let lt_other = self.bin_op(BinOp::Lt(Token![<](span)), &other)?;
-        let lt_other = match lt_other {
-            Value::Bool(b) => b.value,
-            _ => unreachable!(),
+        VALUE_METHODS.iter().chain(kind_methods).copied().collect()
+    }
+
+    fn min_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let other = match inputs {
+            [] => return self.extreme(span, BinOp::Lt(Token![<](span))),
+            [other] => other,
+            _ => return Err(Error::new(span, "expected 0 or 1 arguments")),
         };
 
+        let lt_other =
+            matches!(self.bin_op(BinOp::Lt(Token![<](span)), other)?, Value::Bool(b) if b.value);
+
         Ok(if lt_other { self } else { other.clone() })
     }
 
     fn max_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
-        let &[ref other] = match inputs.try_into() {
-            Ok(inputs) => inputs,
-            _ => return Err(Error::new(span, "expected 1 argument")),
+        let other = match inputs {
+            [] => return self.extreme(span, BinOp::Gt(Token![>](span))),
+            [other] => other,
+            _ => return Err(Error::new(span, "expected 0 or 1 arguments")),
         };
 
-        let gt_other = self.bin_op(BinOp::Gt(Token![>](span)), &other)?;
-        let gt_other = match gt_other {
-            Value::Bool(b) => b.value,
-            _ => unreachable!(),
-        };
+        let gt_other =
+            matches!(self.bin_op(BinOp::Gt(Token![>](span)), other)?, Value::Bool(b) if b.value);
 
         Ok(if gt_other { self } else { other.clone() })
     }
 
     fn clamp_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
-        let &[ref min, ref max] = match inputs.try_into() {
-            Ok(inputs) => inputs,
-            _ => return Err(Error::new(span, "expected 2 arguments")),
-        };
+        let [min, max] = args(inputs, span)?;
 
-        let lt_min = self.bin_op(BinOp::Lt(Token![<](span)), &min)?;
-        let lt_min = match lt_min {
-            Value::Bool(b) => b.value,
-            _ => unreachable!(),
-        };
+        let lt_min =
+            matches!(self.bin_op(BinOp::Lt(Token![<](span)), min)?, Value::Bool(b) if b.value);
 
-        let gt_max = self.bin_op(BinOp::Gt(Token![>](span)), &max)?;
-        let gt_max = match gt_max {
-            Value::Bool(b) => b.value,
-            _ => unreachable!(),
-        };
+        let gt_max =
+            matches!(self.bin_op(BinOp::Gt(Token![>](span)), max)?, Value::Bool(b) if b.value);
 
         Ok(if lt_min {
             min.clone()
@@ -74,10 +254,7 @@ impl<'v> ValueRef<'v> {
     }
 
     fn enumerate_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
-        let &[] = match inputs.try_into() {
-            Ok(inputs) => inputs,
-            _ => return Err(Error::new(span, "expected 0 arguments")),
-        };
+        let [] = args(inputs, span)?;
 
         Ok(match &*self {
             Value::List(self_) => Self::Owned(Value::List(ValueList {
@@ -98,36 +275,1247 @@ impl<'v> ValueRef<'v> {
                     .collect(),
             })),
 
-            _ => return Err(Error::new(span, "expected a list")),
+            _ => return Err(Error::new(span, self.expected("a list"))),
         })
     }
 
     fn index_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<ValueRef<'v>> {
-        let &[ref idx] = match inputs.try_into() {
-            Ok(inputs) => inputs,
-            _ => return Err(Error::new(span, "expected 1 argument")),
-        };
+        let [idx] = args(inputs, span)?;
 
         match &**idx {
-            Value::Int(idx) => {
-                let idx = idx.base10_parse::<usize>().unwrap();
+            Value::Int(idx) if self.text().is_some() => self.char_at(idx.base10_parse()?, span),
+            Value::Int(idx) => self.index_cloned(idx.base10_parse()?, span),
+
+            Value::List(indicies) => Ok(Self::Owned(Value::List(ValueList {
+                span: indicies.span,
+                items: indicies
+                    .clone()
+                    .items
+                    .into_iter()
+                    .map(|idx| Self::index_method(self, span, &[idx]))
+                    .collect::<syn::Result<_>>()?,
+            }))),
+
+            input => Err(Error::new_spanned(input, input.expected("an int"))),
+        }
+    }
+
+    fn slice_method(
+        &self,
+        span: Span,
+        inputs: &[ValueRef<'v>],
+        inclusive: bool,
+    ) -> syn::Result<ValueRef<'v>> {
+        let (start, end) = match inputs {
+            [start] if !inclusive => (int_arg(start)?, None),
+            [start, end] => (int_arg(start)?, Some(int_arg(end)?)),
+
+            _ if inclusive => return Err(Error::new(span, "expected 2 arguments")),
+            _ => return Err(Error::new(span, "expected 1 or 2 arguments")),
+        };
+
+        let len = match self.text() {
+            Some(text) => text.len() as i128,
+            None => self.list_len(span)?,
+        };
+
+        let end = match end {
+            // `..=-1` includes the last item
+            Some(-1) if inclusive => len,
+            Some(end) if inclusive => end + 1,
+            Some(end) => end,
+            None => len,
+        };
+
+        match self.text() {
+            Some(_) => self.map_str(span, |text| {
+                let start = char_boundary(text, start, span)?;
+                let end = char_boundary(text, end, span)?;
+                if start > end {
+                    return Err(Error::new(span, "slice starts after its end"));
+                }
+
+                Ok(text[start..end].to_string())
+            }),
+
+            None => self.slice_cloned(start, end, span),
+        }
+    }
+
+    fn step_by_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [step] = args(inputs, span)?;
+
+        let step = match int_arg(step)? {
+            step if step > 0 => usize::try_from(step).unwrap_or(usize::MAX),
+            _ => return Err(Error::new_spanned(step, "step must be positive")),
+        };
+
+        Ok(list(
+            self.into_items(span)?.into_iter().step_by(step).collect(),
+            span,
+        ))
+    }
+
+    fn list_len(&self, span: Span) -> syn::Result<i128> {
+        Ok(self.list(span)?.items.len() as i128)
+    }
+
+    // List
+
+    fn len_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let len = match self.text() {
+            Some(text) => text.len() as i128,
+            None => self.list_len(span)?,
+        };
+
+        Ok(Self::Owned(int(len, span)))
+    }
+
+    fn is_empty_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let is_empty = match self.text() {
+            Some(text) => text.is_empty(),
+            None => self.list(span)?.items.is_empty(),
+        };
+
+        Ok(Self::Owned(bool(is_empty, span)))
+    }
+
+    fn reverse_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        Ok(list(
+            self.into_items(span)?.into_iter().rev().collect(),
+            span,
+        ))
+    }
+
+    fn contains_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [value] = args(inputs, span)?;
+
+        if let Some(text) = self.text() {
+            let contains = text.contains(&str_arg(value)?);
+
+            return Ok(Self::Owned(bool(contains, span)));
+        }
+
+        let contains = self
+            .list(span)?
+            .items
+            .iter()
+            .any(|item| item.value_eq(value));
+
+        Ok(Self::Owned(bool(contains, span)))
+    }
+
+    fn position_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [value] = args(inputs, span)?;
+
+        match self
+            .list(span)?
+            .items
+            .iter()
+            .position(|item| item.value_eq(value))
+        {
+            Some(idx) => Ok(Self::Owned(int(idx as i128, span))),
+            None => Err(Error::new(span, "the value is not in the list")),
+        }
+    }
+
+    fn first_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        match self.into_items(span)?.into_iter().next() {
+            Some(item) => Ok(item),
+            None => Err(Error::new(span, "the list is empty")),
+        }
+    }
+
+    fn last_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        match self.into_items(span)?.pop() {
+            Some(item) => Ok(item),
+            None => Err(Error::new(span, "the list is empty")),
+        }
+    }
+
+    fn skip_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [count] = args(inputs, span)?;
+        let count = usize_arg(count)?;
+
+        Ok(list(
+            self.into_items(span)?.into_iter().skip(count).collect(),
+            span,
+        ))
+    }
+
+    fn take_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [count] = args(inputs, span)?;
+        let count = usize_arg(count)?;
+
+        Ok(list(
+            self.into_items(span)?.into_iter().take(count).collect(),
+            span,
+        ))
+    }
+
+    fn flatten_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut items = Vec::new();
+        for item in self.into_items(span)? {
+            match &*item {
+                Value::List(_) => items.extend(item.into_items(span)?),
+                _ => return Err(Error::new(span, item.expected("a list of lists"))),
+            }
+        }
+
+        Ok(list(items, span))
+    }
+
+    fn concat_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        if inputs.is_empty() {
+            return Err(Error::new(span, "expected at least 1 argument"));
+        }
+
+        let mut items = self.into_items(span)?;
+        for input in inputs {
+            match &**input {
+                Value::List(input) => items.extend(input.items.iter().cloned()),
+                _ => return Err(Error::new_spanned(input, input.expected("a list"))),
+            }
+        }
+
+        Ok(list(items, span))
+    }
+
+    fn repeat_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [count] = args(inputs, span)?;
+        let count = usize_arg(count)?;
+
+        let items = self.into_items(span)?;
+        let len = items.len();
+
+        Ok(list(
+            items
+                .into_iter()
+                .cycle()
+                .take(checked_len(len.checked_mul(count), span)?)
+                .collect(),
+            span,
+        ))
+    }
+
+    // Combinatorics
+    //
+    // All combinatorics methods emit index tuples in lexicographic order,
+    // so `[a, b, c].combinations(2)` is `[[a, b], [a, c], [b, c]]`.
+
+    fn combinations_method(
+        self,
+        span: Span,
+        inputs: &[ValueRef<'v>],
+        replacement: bool,
+    ) -> syn::Result<Self> {
+        let [len] = args(inputs, span)?;
+        let len = usize_arg(len)?;
+
+        let items = self.into_items(span)?;
+
+        let n = if replacement {
+            (items.len() + len).saturating_sub(1)
+        } else {
+            items.len()
+        };
+        checked_len(binomial(n, len), span)?;
+
+        let mut output = Vec::new();
+        combinations(&items, len, 0, replacement, &mut Vec::new(), &mut output);
+
+        Ok(list(
+            output.into_iter().map(|combo| list(combo, span)).collect(),
+            span,
+        ))
+    }
+
+    fn permutations_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let items = self.into_items(span)?;
+
+        let len = match inputs {
+            [] => items.len(),
+            [len] => usize_arg(len)?,
+            _ => return Err(Error::new(span, "expected 0 or 1 arguments")),
+        };
+
+        let count = (0..len.min(items.len() + 1))
+            .try_fold(1usize, |count, i| count.checked_mul(items.len() - i));
+        checked_len(count, span)?;
+
+        let mut output = Vec::new();
+        permutations(
+            &items,
+            len,
+            &mut vec![false; items.len()],
+            &mut Vec::new(),
+            &mut output,
+        );
+
+        Ok(list(
+            output.into_iter().map(|perm| list(perm, span)).collect(),
+            span,
+        ))
+    }
+
+    /// Subsets ordered by length, then lexicographically.
+    fn powerset_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let items = self.into_items(span)?;
+
+        let count = u32::try_from(items.len())
+            .ok()
+            .and_then(|len| 1usize.checked_shl(len));
+        checked_len(count, span)?;
+
+        let mut output = Vec::new();
+        for len in 0..=items.len() {
+            combinations(&items, len, 0, false, &mut Vec::new(), &mut output);
+        }
+
+        Ok(list(
+            output
+                .into_iter()
+                .map(|subset| list(subset, span))
+                .collect(),
+            span,
+        ))
+    }
+
+    // Reshaping
+    //
+    // Methods that combine lists in parallel (`zip`, `unzip`, `transpose`, `interleave`)
+    // require them to have the same length.
+
+    fn zip_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        Ok(list(
+            self.parallel_items(span, inputs)?
+                .into_iter()
+                .map(|tuple| list(tuple, span))
+                .collect(),
+            span,
+        ))
+    }
+
+    fn unzip_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut lhs = Vec::new();
+        let mut rhs = Vec::new();
+        for item in self.into_items(span)? {
+            let pair = match &*item {
+                Value::List(_) => item.clone().into_items(span)?,
+                _ => Vec::new(),
+            };
+
+            match <[_; 2]>::try_from(pair) {
+                Ok([item_lhs, item_rhs]) => {
+                    lhs.push(item_lhs);
+                    rhs.push(item_rhs);
+                }
+
+                Err(_) => return Err(Error::new_spanned(&item, item.expected("a pair"))),
+            }
+        }
+
+        Ok(list(vec![list(lhs, span), list(rhs, span)], span))
+    }
+
+    fn windows_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [size] = args(inputs, span)?;
+        let size = size_arg(size)?;
+
+        Ok(list(
+            self.into_items(span)?
+                .windows(size)
+                .map(|window| list(window.to_vec(), span))
+                .collect(),
+            span,
+        ))
+    }
+
+    fn chunks_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [size] = args(inputs, span)?;
+        let size = size_arg(size)?;
+
+        Ok(list(
+            self.into_items(span)?
+                .chunks(size)
+                .map(|chunk| list(chunk.to_vec(), span))
+                .collect(),
+            span,
+        ))
+    }
+
+    fn transpose_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut rows = Vec::new();
+        for item in self.into_items(span)? {
+            let row = match &*item {
+                Value::List(_) => item.clone().into_items(span)?,
+                _ => return Err(Error::new_spanned(&item, item.expected("a list"))),
+            };
+
+            if let Some(first_row) = rows.first().map(Vec::len)
+                && row.len() != first_row
+            {
+                return Err(Error::new_spanned(
+                    &item,
+                    item.expected(&format!("a list of {first_row} items")),
+                ));
+            }
+
+            rows.push(row);
+        }
+
+        Ok(list(
+            transpose(rows)
+                .into_iter()
+                .map(|column| list(column, span))
+                .collect(),
+            span,
+        ))
+    }
+
+    fn interleave_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        Ok(list(
+            self.parallel_items(span, inputs)?
+                .into_iter()
+                .flatten()
+                .collect(),
+            span,
+        ))
+    }
+
+    /// Rotates the items to the left, or to the right for negative amounts.
+    fn rotate_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [amount] = args(inputs, span)?;
+        let amount = int_arg(amount)?;
+
+        let mut items = self.into_items(span)?;
+        if !items.is_empty() {
+            let amount = amount.rem_euclid(items.len() as i128) as usize;
+            items.rotate_left(amount);
+        }
+
+        Ok(list(items, span))
+    }
+
+    // Aggregation
+
+    fn sum_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        Ok(Self::Owned(match self.numbers(span)? {
+            Numbers::Ints(items) => int(
+                items
+                    .into_iter()
+                    .try_fold(0i128, i128::checked_add)
+                    .ok_or_else(|| Error::new(span, "integer overflow"))?,
+                span,
+            ),
+            Numbers::Floats(items) => float(items.into_iter().sum(), span)?,
+        }))
+    }
+
+    fn product_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        Ok(Self::Owned(match self.numbers(span)? {
+            Numbers::Ints(items) => int(
+                items
+                    .into_iter()
+                    .try_fold(1i128, i128::checked_mul)
+                    .ok_or_else(|| Error::new(span, "integer overflow"))?,
+                span,
+            ),
+            Numbers::Floats(items) => float(items.into_iter().product(), span)?,
+        }))
+    }
+
+    /// Exclusive prefix sums, so field sizes `[4, 2, 8]` give field offsets `[0, 4, 6]`.
+    fn running_sum_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let items = match self.numbers(span)? {
+            Numbers::Ints(items) => {
+                let mut sum = 0i128;
+                items
+                    .into_iter()
+                    .map(|item| {
+                        let output = int(sum, span);
+                        sum = sum
+                            .checked_add(item)
+                            .ok_or_else(|| Error::new(span, "integer overflow"))?;
+
+                        Ok(Self::Owned(output))
+                    })
+                    .collect::<syn::Result<_>>()?
+            }
+
+            Numbers::Floats(items) => {
+                let mut sum = 0.0;
+                items
+                    .into_iter()
+                    .map(|item| {
+                        let output = float(sum, span)?;
+                        sum += item;
+
+                        Ok(Self::Owned(output))
+                    })
+                    .collect::<syn::Result<_>>()?
+            }
+        };
+
+        Ok(list(items, span))
+    }
+
+    fn all_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut output = true;
+        for item in self.bools(span)? {
+            output &= item;
+        }
+
+        Ok(Self::Owned(bool(output, span)))
+    }
+
+    fn any_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut output = false;
+        for item in self.bools(span)? {
+            output |= item;
+        }
+
+        Ok(Self::Owned(bool(output, span)))
+    }
+
+    fn count_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [value] = args(inputs, span)?;
+
+        let count = self
+            .list(span)?
+            .items
+            .iter()
+            .filter(|item| item.value_eq(value))
+            .count();
+
+        Ok(Self::Owned(int(count as i128, span)))
+    }
+
+    // Sorting and sets
+
+    fn sort_method(self, span: Span, inputs: &[ValueRef<'v>], desc: bool) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut items = self.into_items(span)?;
+        if desc {
+            items.sort_by(|a, b| b.total_cmp(a));
+        } else {
+            items.sort_by(|a, b| a.total_cmp(b));
+        }
+
+        Ok(list(items, span))
+    }
+
+    fn dedup_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let mut items = self.into_items(span)?;
+        items.dedup_by(|a, b| a.value_eq(b));
+
+        Ok(list(items, span))
+    }
+
+    fn unique_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        Ok(list(unique(self.into_items(span)?), span))
+    }
+
+    fn union_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [other] = args(inputs, span)?;
+
+        let mut items = self.into_items(span)?;
+        items.extend(other.list(span)?.items.iter().cloned());
+
+        Ok(list(unique(items), span))
+    }
+
+    /// `intersection` keeps the items that are in `other`, `difference` the items that aren't.
+    fn intersection_method(
+        self,
+        span: Span,
+        inputs: &[ValueRef<'v>],
+        keep_shared: bool,
+    ) -> syn::Result<Self> {
+        let [other] = args(inputs, span)?;
+        let other = &other.list(span)?.items;
+
+        let items = self
+            .into_items(span)?
+            .into_iter()
+            .filter(|item| other.iter().any(|other| other.value_eq(item)) == keep_shared)
+            .collect();
+
+        Ok(list(unique(items), span))
+    }
+
+    fn is_subset_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [other] = args(inputs, span)?;
+        let other = &other.list(span)?.items;
+
+        let is_subset = self
+            .list(span)?
+            .items
+            .iter()
+            .all(|item| other.iter().any(|other| other.value_eq(item)));
+
+        Ok(Self::Owned(bool(is_subset, span)))
+    }
+
+    // Strings
+
+    fn starts_with_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [pat] = args(inputs, span)?;
+
+        let starts_with = self.str(span)?.starts_with(&str_arg(pat)?);
+
+        Ok(Self::Owned(bool(starts_with, span)))
+    }
+
+    fn ends_with_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [pat] = args(inputs, span)?;
+
+        let ends_with = self.str(span)?.ends_with(&str_arg(pat)?);
+
+        Ok(Self::Owned(bool(ends_with, span)))
+    }
+
+    /// The byte index of the first occurrence of a pattern.
+    fn find_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [pat] = args(inputs, span)?;
+
+        match self.str(span)?.find(&str_arg(pat)?) {
+            Some(idx) => Ok(Self::Owned(int(idx as i128, span))),
+            None => Err(Error::new_spanned(pat, "the pattern is not in the string")),
+        }
+    }
+
+    fn replace_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [from, to] = args(inputs, span)?;
+        let from = str_arg(from)?;
+        let to = str_arg(to)?;
+
+        self.map_str(span, |str| Ok(str.replace(&from, &to)))
+    }
+
+    /// Splits a string into a list of strings, or an ident into a list of idents,
+    /// where the numeric parts of an ident become ints (`u8x16.split(x)` is `[u8, 16]`).
+    fn split_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [sep] = args(inputs, span)?;
+        let sep = str_arg(sep)?;
+
+        let items = match &**self {
+            Value::Str(self_) => self_
+                .value()
+                .split(&sep)
+                .map(|part| Self::Owned(string(part, span)))
+                .collect(),
+
+            Value::Ident(self_) => self_
+                .str()
+                .split(&sep)
+                .map(|part| {
+                    Ok(Self::Owned(match part.parse::<i128>() {
+                        Ok(part) if part >= 0 => int(part, span),
+                        _ => ident(part, span)?,
+                    }))
+                })
+                .collect::<syn::Result<_>>()?,
+
+            _ => return Err(Error::new(span, self.expected("a string or an ident"))),
+        };
+
+        Ok(list(items, span))
+    }
+
+    fn strip_prefix_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [prefix] = args(inputs, span)?;
+        let prefix_str = str_arg(prefix)?;
+
+        self.map_str(span, |str| match str.strip_prefix(&prefix_str) {
+            Some(str) => Ok(str.to_string()),
+            None => Err(Error::new_spanned(
+                prefix,
+                format!("`{str}` doesn't start with `{prefix_str}`"),
+            )),
+        })
+    }
+
+    fn strip_suffix_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [suffix] = args(inputs, span)?;
+        let suffix_str = str_arg(suffix)?;
+
+        self.map_str(span, |str| match str.strip_suffix(&suffix_str) {
+            Some(str) => Ok(str.to_string()),
+            None => Err(Error::new_spanned(
+                suffix,
+                format!("`{str}` doesn't end with `{suffix_str}`"),
+            )),
+        })
+    }
+
+    fn format_method(
+        &self,
+        span: Span,
+        inputs: &[ValueRef<'v>],
+        namespace: &Namespace<'v, 'v>,
+    ) -> syn::Result<Self> {
+        let template = match &**self {
+            Value::Str(template) => template,
+            _ => return Err(Error::new(span, self.expected("a format string"))),
+        };
+
+        let output = format(&template.value(), inputs, namespace, template.span())?;
+
+        Ok(Self::Owned(string(output, span)))
+    }
+
+    fn is_keyword_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        Ok(Self::Owned(bool(is_keyword(&self.str(span)?), span)))
+    }
+
+    fn is_valid_ident_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        Ok(Self::Owned(bool(is_valid_ident(&self.str(span)?), span)))
+    }
+
+    fn chars_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        let items = self
+            .str(span)?
+            .chars()
+            .map(|c| Self::Owned(char(c, span)))
+            .collect();
+
+        Ok(list(items, span))
+    }
+
+    fn char_at_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [idx] = args(inputs, span)?;
+
+        self.char_at(int_arg(idx)?, span)
+    }
+
+    /// The char at a byte index, where negative indices count from the end.
+    fn char_at(&self, idx: i128, span: Span) -> syn::Result<Self> {
+        let text = self.str(span)?;
+
+        let idx = char_boundary(&text, idx, span)?;
+        match text[idx..].chars().next() {
+            Some(c) => Ok(Self::Owned(char(c, span))),
+            None => Err(Error::new(span, "index is out of bounds")),
+        }
+    }
+
+    fn trim_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        self.map_str(span, |str| Ok(str.trim().to_string()))
+    }
+
+    fn to_upper_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        self.map_str(span, |str| Ok(str.to_uppercase()))
+    }
+
+    fn to_lower_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        self.map_str(span, |str| Ok(str.to_lowercase()))
+    }
+
+    fn case_method(&self, span: Span, inputs: &[ValueRef<'v>], case: Case) -> syn::Result<Self> {
+        let [] = args(inputs, span)?;
+
+        self.map_str(span, |str| Ok(case.convert(str)))
+    }
+
+    /// Maps the text of a string or an ident, keeping its kind.
+    fn map_str(
+        &self,
+        span: Span,
+        f: impl FnOnce(&str) -> syn::Result<String>,
+    ) -> syn::Result<Self> {
+        Ok(Self::Owned(match &**self {
+            Value::Str(self_) => string(f(&self_.value())?, span),
+            Value::Ident(self_) => ident(f(self_.str())?, span)?,
+
+            _ => return Err(Error::new(span, self.expected("a string or an ident"))),
+        }))
+    }
+
+    fn str(&self, span: Span) -> syn::Result<String> {
+        self.text()
+            .ok_or_else(|| Error::new(span, self.expected("a string or an ident")))
+    }
+
+    /// The text of a string or an ident.
+    fn text(&self) -> Option<String> {
+        match &**self {
+            Value::Str(self_) => Some(self_.value()),
+            Value::Ident(self_) => Some(self_.str().to_string()),
+            _ => None,
+        }
+    }
+
+    // Closures
+
+    fn map_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        let items = self
+            .into_items(span)?
+            .into_iter()
+            .map(|item| f.call(&[item]))
+            .collect::<syn::Result<_>>()?;
+
+        Ok(list(items, span))
+    }
+
+    fn filter_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        let mut items = Vec::new();
+        for item in self.into_items(span)? {
+            if call_bool(f, &item)? {
+                items.push(item);
+            }
+        }
+
+        Ok(list(items, span))
+    }
+
+    /// Like `filter` and `map` in one,
+    /// where the closure returns `[value]` to keep `value` or `[]` to skip the item.
+    fn filter_map_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        let mut items = Vec::new();
+        for item in self.into_items(span)? {
+            let output = f.call(&[item])?;
+
+            match &*output {
+                Value::List(output) if output.items.len() <= 1 => {
+                    items.extend(output.items.first().cloned())
+                }
+
+                _ => {
+                    return Err(Error::new_spanned(
+                        f,
+                        output.expected("the closure to return `[]` or `[value]`"),
+                    ));
+                }
+            }
+        }
+
+        Ok(list(items, span))
+    }
+
+    fn flat_map_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        let mut items = Vec::new();
+        for item in self.into_items(span)? {
+            match f.call(&[item])? {
+                ValueRef::Owned(Value::List(output)) => items.extend(output.items),
+                output => {
+                    return Err(Error::new_spanned(
+                        f,
+                        output.expected("the closure to return a list"),
+                    ));
+                }
+            }
+        }
+
+        Ok(list(items, span))
+    }
+
+    fn fold_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [init, f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        let mut output = init.clone();
+        for item in self.into_items(span)? {
+            output = f.call(&[output, item])?;
+        }
+
+        Ok(output)
+    }
 
-                Ok(self.index_cloned(idx, span)?)
+    fn any_by_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        for item in &self.list(span)?.items {
+            if call_bool(f, item)? {
+                return Ok(Self::Owned(bool(true, span)));
+            }
+        }
+
+        Ok(Self::Owned(bool(false, span)))
+    }
+
+    fn all_by_method(&self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        for item in &self.list(span)?.items {
+            if !call_bool(f, item)? {
+                return Ok(Self::Owned(bool(false, span)));
+            }
+        }
+
+        Ok(Self::Owned(bool(true, span)))
+    }
+
+    fn sort_by_key_method(self, span: Span, inputs: &[ValueRef<'v>]) -> syn::Result<Self> {
+        let [f] = args(inputs, span)?;
+        let f = f.closure(span)?;
+
+        let mut items = self
+            .into_items(span)?
+            .into_iter()
+            .map(|item| Ok((f.call(from_ref(&item))?, item)))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        items.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Ok(list(
+            items.into_iter().map(|(_, item)| item).collect(),
+            span,
+        ))
+    }
+
+    fn closure(&self, span: Span) -> syn::Result<&Closure> {
+        match &**self {
+            Value::Closure(self_) => Ok(self_),
+            _ => Err(Error::new(span, self.expected("a closure"))),
+        }
+    }
+
+    /// The smallest (`Lt`) or largest (`Gt`) item of a list.
+    fn extreme(self, span: Span, op: BinOp) -> syn::Result<Self> {
+        let mut items = self.into_items(span)?.into_iter();
+
+        let mut output = match items.next() {
+            Some(first) => first,
+            None => return Err(Error::new(span, "the list is empty")),
+        };
+
+        for item in items {
+            if discriminant(&*item) != discriminant(&*output) {
+                return Err(Error::new_spanned(
+                    &item,
+                    format!(
+                        "cannot compare {} with {}",
+                        item.describe(),
+                        output.describe()
+                    ),
+                ));
+            }
+
+            if let Value::Bool(is_extreme) = item.bin_op(op, &output)?
+                && is_extreme.value
+            {
+                output = item;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// The items of a list of numbers, which are either all ints or all floats.
+    fn numbers(&self, span: Span) -> syn::Result<Numbers> {
+        let items = &self.list(span)?.items;
+
+        Ok(match items.first().map(|item| &**item) {
+            Some(Value::Float(_)) => Numbers::Floats(
+                items
+                    .iter()
+                    .map(|item| match &**item {
+                        Value::Float(item) => item.base10_parse(),
+                        _ => Err(Error::new_spanned(
+                            item,
+                            item.expected("a float like the first item"),
+                        )),
+                    })
+                    .collect::<syn::Result<_>>()?,
+            ),
+
+            _ => Numbers::Ints(
+                items
+                    .iter()
+                    .map(|item| match &**item {
+                        Value::Int(item) => item.base10_parse(),
+                        Value::Float(_) => Err(Error::new_spanned(
+                            item,
+                            item.expected("an int like the first item"),
+                        )),
+                        _ => Err(Error::new_spanned(item, item.expected("an int or a float"))),
+                    })
+                    .collect::<syn::Result<_>>()?,
+            ),
+        })
+    }
+
+    fn bools(&self, span: Span) -> syn::Result<Vec<bool>> {
+        self.list(span)?
+            .items
+            .iter()
+            .map(|item| match &**item {
+                Value::Bool(item) => Ok(item.value),
+                _ => Err(Error::new_spanned(item, item.expected("a bool"))),
+            })
+            .collect()
+    }
+
+    /// Groups the items of `self` and `others` by position, erroring if their lengths differ.
+    fn parallel_items(self, span: Span, others: &[ValueRef<'v>]) -> syn::Result<Vec<Vec<Self>>> {
+        if others.is_empty() {
+            return Err(Error::new(span, "expected at least 1 argument"));
+        }
+
+        let rows = once(Ok(self.into_items(span)?))
+            .chain(others.iter().map(|other| other.clone().into_items(span)))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        for (other, row) in others.iter().zip(&rows[1..]) {
+            if row.len() != rows[0].len() {
+                return Err(Error::new_spanned(
+                    other,
+                    other.expected(&format!("a list of {} items", rows[0].len())),
+                ));
             }
+        }
 
-            Value::List(indicies) => {
-                return Ok(Self::Owned(Value::List(ValueList {
-                    span: indicies.span,
-                    items: indicies
-                        .clone()
-                        .items
-                        .into_iter()
-                        .map(|idx| Self::index_method(self, span, &[idx]))
-                        .collect::<syn::Result<_>>()?,
-                })));
+        Ok(transpose(rows))
+    }
+
+    fn list(&self, span: Span) -> syn::Result<&ValueList<'v>> {
+        match &**self {
+            Value::List(self_) => Ok(self_),
+            _ => Err(Error::new(span, self.expected("a list"))),
+        }
+    }
+
+    /// Splits a list into its items, keeping them borrowed if the list is borrowed.
+    pub(super) fn into_items(self, span: Span) -> syn::Result<Vec<Self>> {
+        match self {
+            Self::Owned(Value::List(self_)) => Ok(self_.items),
+            Self::Ref(Value::List(self_)) => {
+                Ok(self_.items.iter().map(|item| Self::Ref(item)).collect())
             }
 
-            input => return Err(Error::new_spanned(input, "expected an int")),
+            _ => Err(Error::new(span, self.expected("a list"))),
+        }
+    }
+}
+
+enum Numbers {
+    Ints(Vec<i128>),
+    Floats(Vec<f64>),
+}
+
+fn call_bool<'v>(f: &Closure, item: &ValueRef<'v>) -> syn::Result<bool> {
+    match &*f.call(from_ref(item))? {
+        Value::Bool(output) => Ok(output.value),
+        output => Err(Error::new_spanned(
+            f,
+            output.expected("the closure to return a bool"),
+        )),
+    }
+}
+
+/// Removes every item that is equal to an earlier item.
+fn unique<'v>(items: Vec<ValueRef<'v>>) -> Vec<ValueRef<'v>> {
+    let mut output = Vec::<ValueRef>::with_capacity(items.len());
+    for item in items {
+        if !output.iter().any(|other| other.value_eq(&item)) {
+            output.push(item);
+        }
+    }
+
+    output
+}
+
+pub(super) fn list<'v>(items: Vec<ValueRef<'v>>, span: Span) -> ValueRef<'v> {
+    ValueRef::Owned(Value::List(ValueList { span, items }))
+}
+
+fn transpose<T>(rows: Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let len = rows.first().map_or(0, Vec::len);
+
+    let mut columns = (0..len).map(|_| Vec::new()).collect::<Vec<_>>();
+    for row in rows {
+        for (column, item) in columns.iter_mut().zip(row) {
+            column.push(item);
+        }
+    }
+
+    columns
+}
+
+fn combinations<'v>(
+    items: &[ValueRef<'v>],
+    len: usize,
+    start: usize,
+    replacement: bool,
+    current: &mut Vec<ValueRef<'v>>,
+    output: &mut Vec<Vec<ValueRef<'v>>>,
+) {
+    if current.len() == len {
+        output.push(current.clone());
+        return;
+    }
+
+    for idx in start..items.len() {
+        current.push(items[idx].clone());
+
+        let next_start = if replacement { idx } else { idx + 1 };
+        combinations(items, len, next_start, replacement, current, output);
+
+        current.pop();
+    }
+}
+
+/// The number of `k`-combinations of `n` items, or `None` if it overflows.
+fn binomial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+
+    (0..k.min(n - k)).try_fold(1usize, |count, i| Some(count.checked_mul(n - i)? / (i + 1)))
+}
+
+fn permutations<'v>(
+    items: &[ValueRef<'v>],
+    len: usize,
+    used: &mut [bool],
+    current: &mut Vec<ValueRef<'v>>,
+    output: &mut Vec<Vec<ValueRef<'v>>>,
+) {
+    if current.len() == len {
+        output.push(current.clone());
+        return;
+    }
+
+    for idx in 0..items.len() {
+        if used[idx] {
+            continue;
         }
+
+        used[idx] = true;
+        current.push(items[idx].clone());
+
+        permutations(items, len, used, current, output);
+
+        current.pop();
+        used[idx] = false;
     }
 }
+
+/// Resolves a possibly negative byte index of `text`, which has to be at a char boundary.
+fn char_boundary(text: &str, idx: i128, span: Span) -> syn::Result<usize> {
+    let len = text.len() as i128;
+    let resolved = if idx < 0 { len + idx } else { idx };
+
+    if !(0..=len).contains(&resolved) {
+        return Err(Error::new(span, "index is out of bounds"));
+    }
+    if !text.is_char_boundary(resolved as usize) {
+        return Err(Error::new(
+            span,
+            format!("byte index {idx} is not a char boundary of `{text}`"),
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+fn str_arg(input: &Value) -> syn::Result<String> {
+    match input {
+        Value::Str(input) => Ok(input.value()),
+        Value::Ident(input) => Ok(input.str().to_string()),
+        Value::Char(input) => Ok(input.value().to_string()),
+        _ => Err(Error::new_spanned(
+            input,
+            input.expected("a string, an ident or a char"),
+        )),
+    }
+}
+
+fn int_arg(input: &Value) -> syn::Result<i128> {
+    match input {
+        Value::Int(input) => input.base10_parse(),
+        _ => Err(Error::new_spanned(input, input.expected("an int"))),
+    }
+}
+
+fn usize_arg(input: &Value) -> syn::Result<usize> {
+    match int_arg(input)? {
+        input if input >= 0 => Ok(usize::try_from(input).unwrap_or(usize::MAX)),
+        _ => Err(Error::new_spanned(
+            input,
+            input.expected("a non-negative int"),
+        )),
+    }
+}
+
+fn size_arg(input: &Value) -> syn::Result<usize> {
+    match usize_arg(input)? {
+        0 => Err(Error::new_spanned(input, input.expected("a positive int"))),
+        input => Ok(input),
+    }
+}
+
+fn args<'a, 'v, const N: usize>(
+    inputs: &'a [ValueRef<'v>],
+    span: Span,
+) -> syn::Result<&'a [ValueRef<'v>; N]> {
+    inputs.try_into().map_err(|_| {
+        Error::new(
+            span,
+            match N {
+                1 => "expected 1 argument".to_string(),
+                _ => format!("expected {N} arguments"),
+            },
+        )
+    })
+}
@@ -1,10 +1,17 @@
 use super::{expr::*, fragment::*, name::*, *};
 
+mod closure;
+mod format;
+mod functions;
 mod methods;
 mod ops;
+mod preview;
 mod value;
 mod value_list;
 mod value_ref;
+pub use closure::*;
+pub use format::format;
+use ops::{bool, char, checked_len, float, ident, int, string};
 pub use value::*;
 pub use value_list::*;
 pub use value_ref::*;
@@ -1,17 +1,22 @@
-use std::ffi::CStr;
+use std::{cmp::Ordering, ffi::CStr, rc::Rc};
 
 use proc_macro2::Span;
-use syn::{Error, LitBool, LitFloat, LitInt, LitStr};
+use quote::ToTokens;
+use syn::{Error, LitBool, LitChar, LitFloat, LitInt, LitStr};
 
 use super::*;
 
 impl<'a> Value<'a> {
     pub fn bin_op(&self, op: BinOp, rhs: &Self) -> syn::Result<Self> {
-        Ok(match (self, rhs) {
+        if let BinOp::In(_) = op {
+            return self.in_op(op, rhs);
+        }
+
+        let output = match (self, rhs) {
             (Self::Bool(lhs), Self::Bool(rhs)) => Self::bool_bin_op(lhs.value, op, rhs.value)?,
 
             (Self::Int(lhs), Self::Int(rhs)) => {
-                Self::int_bin_op(lhs.base10_parse::<u128>()?, op, rhs.base10_parse::<u128>()?)?
+                Self::int_bin_op(lhs.base10_parse::<i128>()?, op, rhs.base10_parse::<i128>()?)?
             }
 
             (Self::Float(lhs), Self::Float(rhs)) => {
@@ -19,7 +24,7 @@ impl<'a> Value<'a> {
             }
 
             (Self::Str(lhs), Self::Str(rhs)) => Self::str_bin_op(&lhs.value(), op, &rhs.value())?,
-            (Self::Str(lhs), Self::Ident(rhs)) => Self::str_bin_op(&lhs.value(), op, &rhs.str())?,
+            (Self::Str(lhs), Self::Ident(rhs)) => Self::str_bin_op(&lhs.value(), op, rhs.str())?,
             (Self::Str(lhs), Self::Char(rhs)) => {
                 Self::str_bin_op(&lhs.value(), op, &rhs.value().to_string())?
             }
@@ -34,30 +39,199 @@ impl<'a> Value<'a> {
                 Self::byte_str_bin_op(&lhs.value(), op, &rhs.value())?
             }
 
-            (Self::Ident(lhs), Self::Ident(rhs)) => Self::ident_bin_op(&lhs.str(), op, &rhs.str())?,
+            (Self::Ident(lhs), Self::Ident(rhs)) => Self::ident_bin_op(lhs.str(), op, rhs.str())?,
+
+            (Self::List(lhs), Self::List(rhs)) => Self::list_bin_op(lhs, op, rhs)?,
+            (Self::List(lhs), Self::Int(rhs)) | (Self::Int(rhs), Self::List(lhs)) => {
+                Self::list_int_bin_op(lhs, op, count(rhs)?)?
+            }
+
+            (Self::Str(lhs), Self::Int(rhs)) | (Self::Int(rhs), Self::Str(lhs)) => {
+                Self::str_int_bin_op(&lhs.value(), op, count(rhs)?)?
+            }
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
+            _ => None,
+        };
+
+        output.ok_or_else(|| {
+            Error::new_spanned(
+                op,
+                format!(
+                    "cannot apply `{}` to {} and {}",
+                    op.to_token_stream(),
+                    self.describe(),
+                    rhs.describe()
+                ),
+            )
         })
     }
 
     pub fn un_op(&self, op: UnOp) -> syn::Result<Self> {
+        Ok(match (op, self) {
+            (UnOp::Neg(_), Self::Int(base)) => match base.base10_parse::<i128>()?.checked_neg() {
+                Some(value) => int(value, op.span()),
+                None => return Err(Error::new_spanned(op, "attempt to negate with overflow")),
+            },
+            (UnOp::Neg(_), Self::Float(base)) => float(-base.base10_parse::<f64>()?, op.span())?,
+
+            (UnOp::Not(_), Self::Int(base)) => int(!base.base10_parse::<i128>()?, op.span()),
+            (UnOp::Not(_), Self::Bool(base)) => bool(!base.value, op.span()),
+
+            _ => {
+                return Err(Error::new_spanned(
+                    op,
+                    format!(
+                        "cannot apply `{}` to {}",
+                        op.to_token_stream(),
+                        self.describe()
+                    ),
+                ));
+            }
+        })
+    }
+
+    pub fn value_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bool(lhs), Self::Bool(rhs)) => lhs.value == rhs.value,
+            (Self::Int(lhs), Self::Int(rhs)) => lhs.base10_digits() == rhs.base10_digits(),
+            (Self::Float(lhs), Self::Float(rhs)) => {
+                lhs.base10_parse::<f64>().ok() == rhs.base10_parse::<f64>().ok()
+            }
+            (Self::Str(lhs), Self::Str(rhs)) => lhs.value() == rhs.value(),
+            (Self::Char(lhs), Self::Char(rhs)) => lhs.value() == rhs.value(),
+            (Self::CStr(lhs), Self::CStr(rhs)) => lhs.value() == rhs.value(),
+            (Self::ByteStr(lhs), Self::ByteStr(rhs)) => lhs.value() == rhs.value(),
+            (Self::Ident(lhs), Self::Ident(rhs)) => lhs.str() == rhs.str(),
+
+            (Self::List(lhs), Self::List(rhs)) => {
+                lhs.items.len() == rhs.items.len()
+                    && lhs
+                        .items
+                        .iter()
+                        .zip(&rhs.items)
+                        .all(|(lhs, rhs)| lhs.value_eq(rhs))
+            }
+
+            (Self::Closure(lhs), Self::Closure(rhs)) => Rc::ptr_eq(lhs, rhs),
+
+            _ => false,
+        }
+    }
+
+    /// A total order over every kind of value, used for sorting.
+    ///
+    /// Values of different kinds are ordered by kind,
+    /// and values of the same kind by their natural order.
+    /// Lists are ordered lexicographically.
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Bool(lhs), Self::Bool(rhs)) => lhs.value.cmp(&rhs.value),
+            (Self::Int(lhs), Self::Int(rhs)) => {
+                match (lhs.base10_parse::<i128>(), rhs.base10_parse::<i128>()) {
+                    (Ok(lhs), Ok(rhs)) => lhs.cmp(&rhs),
+                    _ => lhs.base10_digits().cmp(rhs.base10_digits()),
+                }
+            }
+            (Self::Float(lhs), Self::Float(rhs)) => {
+                let lhs = lhs.base10_parse::<f64>().unwrap_or(f64::NAN);
+                let rhs = rhs.base10_parse::<f64>().unwrap_or(f64::NAN);
+
+                lhs.total_cmp(&rhs)
+            }
+            (Self::Str(lhs), Self::Str(rhs)) => lhs.value().cmp(&rhs.value()),
+            (Self::Char(lhs), Self::Char(rhs)) => lhs.value().cmp(&rhs.value()),
+            (Self::CStr(lhs), Self::CStr(rhs)) => lhs.value().cmp(&rhs.value()),
+            (Self::ByteStr(lhs), Self::ByteStr(rhs)) => lhs.value().cmp(&rhs.value()),
+            (Self::Ident(lhs), Self::Ident(rhs)) => lhs.str().cmp(rhs.str()),
+
+            (Self::List(lhs), Self::List(rhs)) => {
+                for (lhs, rhs) in lhs.items.iter().zip(&rhs.items) {
+                    match lhs.total_cmp(rhs) {
+                        Ordering::Equal => {}
+                        ordering => return ordering,
+                    }
+                }
+
+                lhs.items.len().cmp(&rhs.items.len())
+            }
+
+            _ => self.kind_rank().cmp(&other.kind_rank()),
+        }
+    }
+
+    fn kind_rank(&self) -> u8 {
         match self {
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
+            Self::Bool(_) => 0,
+            Self::Int(_) => 1,
+            Self::Float(_) => 2,
+            Self::Char(_) => 3,
+            Self::Str(_) => 4,
+            Self::CStr(_) => 5,
+            Self::ByteStr(_) => 6,
+            Self::Ident(_) => 7,
+            Self::List(_) => 8,
+            Self::Closure(_) => 9,
         }
     }
 
-    fn int_bin_op(lhs: u128, op: BinOp, rhs: u128) -> syn::Result<Self> {
-        Ok(match op {
-            BinOp::Add(_) => int(lhs + rhs, op.span()),
-            BinOp::Sub(_) => int(lhs - rhs, op.span()),
-            BinOp::Mul(_) => int(lhs * rhs, op.span()),
-            BinOp::Div(_) => int(lhs / rhs, op.span()),
-            BinOp::Rem(_) => int(lhs % rhs, op.span()),
+    fn in_op(&self, op: BinOp, rhs: &Self) -> syn::Result<Self> {
+        Ok(match (self, rhs) {
+            (_, Self::List(rhs)) => {
+                bool(rhs.items.iter().any(|item| self.value_eq(item)), op.span())
+            }
+
+            (Self::Str(_) | Self::Ident(_) | Self::Char(_), Self::Str(_) | Self::Ident(_)) => bool(
+                rhs.try_to_string()?.contains(&self.try_to_string()?),
+                op.span(),
+            ),
+
+            _ => {
+                return Err(Error::new_spanned(
+                    op,
+                    format!(
+                        "cannot apply `in` to {} and {}. expected a list or a string",
+                        self.describe(),
+                        rhs.describe()
+                    ),
+                ));
+            }
+        })
+    }
+
+    fn int_bin_op(lhs: i128, op: BinOp, rhs: i128) -> syn::Result<Option<Self>> {
+        let checked = |output: Option<i128>, message: &str| match output {
+            Some(output) => Ok(int(output, op.span())),
+            None => Err(Error::new_spanned(op, message)),
+        };
+
+        let shift = u32::try_from(rhs).ok().filter(|rhs| *rhs < i128::BITS);
+
+        Ok(Some(match op {
+            BinOp::Add(_) => checked(lhs.checked_add(rhs), "attempt to add with overflow")?,
+            BinOp::Sub(_) => checked(lhs.checked_sub(rhs), "attempt to subtract with overflow")?,
+            BinOp::Mul(_) => checked(lhs.checked_mul(rhs), "attempt to multiply with overflow")?,
+            BinOp::Div(_) if rhs == 0 => checked(None, "attempt to divide by zero")?,
+            BinOp::Div(_) => checked(lhs.checked_div(rhs), "attempt to divide with overflow")?,
+            BinOp::Rem(_) if rhs == 0 => checked(
+                None,
+                "attempt to calculate the remainder with a divisor of zero",
+            )?,
+            BinOp::Rem(_) => checked(
+                lhs.checked_rem(rhs),
+                "attempt to calculate the remainder with overflow",
+            )?,
 
             BinOp::BitAnd(_) => int(lhs & rhs, op.span()),
             BinOp::BitOr(_) => int(lhs | rhs, op.span()),
-            BinOp::Shl(_) => int(lhs << rhs, op.span()),
-            BinOp::Shr(_) => int(lhs >> rhs, op.span()),
+            BinOp::BitXor(_) => int(lhs ^ rhs, op.span()),
+            BinOp::Shl(_) => checked(
+                shift.map(|rhs| lhs << rhs),
+                "attempt to shift left with overflow",
+            )?,
+            BinOp::Shr(_) => checked(
+                shift.map(|rhs| lhs >> rhs),
+                "attempt to shift right with overflow",
+            )?,
 
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
@@ -66,31 +240,20 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            BinOp::Range(_) => Self::List(ValueList {
-                span: op.span(),
-                items: (lhs..rhs)
-                    .map(|i| ValueRef::Owned(int(i, op.span())))
-                    .collect(),
-            }),
-
-            BinOp::RangeInclusive(_) => Self::List(ValueList {
-                span: op.span(),
-                items: (lhs..=rhs)
-                    .map(|i| ValueRef::Owned(int(i, op.span())))
-                    .collect(),
-            }),
+            BinOp::Range(_) => int_range(lhs, rhs, false, op.span())?,
+            BinOp::RangeInclusive(_) => int_range(lhs, rhs, true, op.span())?,
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
     }
 
-    fn float_bin_op(lhs: f64, op: BinOp, rhs: f64) -> syn::Result<Self> {
-        Ok(match op {
-            BinOp::Add(_) => float(lhs + rhs, op.span()),
-            BinOp::Sub(_) => float(lhs - rhs, op.span()),
-            BinOp::Mul(_) => float(lhs * rhs, op.span()),
-            BinOp::Div(_) => float(lhs / rhs, op.span()),
-            BinOp::Rem(_) => float(lhs % rhs, op.span()),
+    fn float_bin_op(lhs: f64, op: BinOp, rhs: f64) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
+            BinOp::Add(_) => float(lhs + rhs, op.span())?,
+            BinOp::Sub(_) => float(lhs - rhs, op.span())?,
+            BinOp::Mul(_) => float(lhs * rhs, op.span())?,
+            BinOp::Div(_) => float(lhs / rhs, op.span())?,
+            BinOp::Rem(_) => float(lhs % rhs, op.span())?,
 
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
@@ -99,29 +262,29 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
     }
 
-    fn bool_bin_op(lhs: bool, op: BinOp, rhs: bool) -> syn::Result<Self> {
-        Ok(match op {
+    fn bool_bin_op(lhs: bool, op: BinOp, rhs: bool) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
             BinOp::BitAnd(_) | BinOp::LogicalAnd(_) => bool(lhs & rhs, op.span()),
             BinOp::BitOr(_) | BinOp::LogicalOr(_) => bool(lhs | rhs, op.span()),
             BinOp::BitXor(_) => bool(lhs ^ rhs, op.span()),
 
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
-            BinOp::Lt(_) => bool(lhs < rhs, op.span()),
-            BinOp::Gt(_) => bool(lhs > rhs, op.span()),
+            BinOp::Lt(_) => bool(!lhs & rhs, op.span()),
+            BinOp::Gt(_) => bool(lhs & !rhs, op.span()),
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
     }
 
-    fn str_bin_op(lhs: &str, op: BinOp, rhs: &str) -> syn::Result<Self> {
-        Ok(match op {
+    fn str_bin_op(lhs: &str, op: BinOp, rhs: &str) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
             BinOp::Add(_) => string(lhs.to_string() + rhs, op.span()),
 
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
@@ -131,12 +294,24 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
+    }
+
+    fn str_int_bin_op(lhs: &str, op: BinOp, rhs: usize) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
+            BinOp::Mul(_) => {
+                checked_len(lhs.len().checked_mul(rhs), op.span())?;
+
+                string(lhs.repeat(rhs), op.span())
+            }
+
+            _ => return Ok(None),
+        }))
     }
 
-    fn char_bin_op(lhs: char, op: BinOp, rhs: char) -> syn::Result<Self> {
-        Ok(match op {
+    fn char_bin_op(lhs: char, op: BinOp, rhs: char) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
             BinOp::Lt(_) => bool(lhs < rhs, op.span()),
@@ -144,12 +319,12 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
     }
 
-    fn cstr_bin_op(lhs: &CStr, op: BinOp, rhs: &CStr) -> syn::Result<Self> {
-        Ok(match op {
+    fn cstr_bin_op(lhs: &CStr, op: BinOp, rhs: &CStr) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
             BinOp::Lt(_) => bool(lhs < rhs, op.span()),
@@ -157,12 +332,12 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
     }
 
-    fn byte_str_bin_op(lhs: &[u8], op: BinOp, rhs: &[u8]) -> syn::Result<Self> {
-        Ok(match op {
+    fn byte_str_bin_op(lhs: &[u8], op: BinOp, rhs: &[u8]) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
             BinOp::Lt(_) => bool(lhs < rhs, op.span()),
@@ -170,13 +345,13 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
     }
 
-    fn ident_bin_op(lhs: &str, op: BinOp, rhs: &str) -> syn::Result<Self> {
-        Ok(match op {
-            BinOp::Add(_) => ident(lhs.to_string() + rhs, op.span()),
+    fn ident_bin_op(lhs: &str, op: BinOp, rhs: &str) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
+            BinOp::Add(_) => ident(lhs.to_string() + rhs, op.span())?,
 
             BinOp::Eq(_) => bool(lhs == rhs, op.span()),
             BinOp::Ne(_) => bool(lhs != rhs, op.span()),
@@ -185,27 +360,111 @@ impl<'a> Value<'a> {
             BinOp::Le(_) => bool(lhs <= rhs, op.span()),
             BinOp::Ge(_) => bool(lhs >= rhs, op.span()),
 
-            _ => return Err(Error::new_spanned(op, "invalid operation")),
-        })
+            _ => return Ok(None),
+        }))
+    }
+
+    fn list_bin_op(
+        lhs: &ValueList<'a>,
+        op: BinOp,
+        rhs: &ValueList<'a>,
+    ) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
+            BinOp::Add(_) => Self::List(ValueList {
+                span: op.span(),
+                items: lhs.items.iter().chain(&rhs.items).cloned().collect(),
+            }),
+
+            _ => return Ok(None),
+        }))
+    }
+
+    fn list_int_bin_op(lhs: &ValueList<'a>, op: BinOp, rhs: usize) -> syn::Result<Option<Self>> {
+        Ok(Some(match op {
+            BinOp::Mul(_) => Self::List(ValueList {
+                span: op.span(),
+                items: lhs
+                    .items
+                    .iter()
+                    .cycle()
+                    .take(checked_len(lhs.items.len().checked_mul(rhs), op.span())?)
+                    .cloned()
+                    .collect(),
+            }),
+
+            _ => return Ok(None),
+        }))
     }
 }
 
-fn int(value: u128, span: Span) -> Value<'static> {
+pub(super) fn int(value: i128, span: Span) -> Value<'static> {
     Value::Int(LitInt::new(&value.to_string(), span))
 }
 
-fn float(value: f64, span: Span) -> Value<'static> {
-    Value::Float(LitFloat::new(&value.to_string(), span))
+/// Float literals can't be infinite or NaN, so results like `1.0 / 0.0` are errors.
+pub(super) fn float(value: f64, span: Span) -> syn::Result<Value<'static>> {
+    if !value.is_finite() {
+        return Err(Error::new(
+            span,
+            format!("`{value}` is not a valid float literal"),
+        ));
+    }
+
+    Ok(Value::Float(LitFloat::new(&format!("{value:?}"), span)))
+}
+
+/// The most items (or bytes) a range, repeat or combinatoric method may produce,
+/// which keeps a typo like `0..1 << 60` from exhausting memory.
+pub(super) const MAX_LEN: usize = 1 << 20;
+
+/// Checks the length of a result before it is built.
+/// `None` means the length calculation itself overflowed.
+pub(super) fn checked_len(len: Option<usize>, span: Span) -> syn::Result<usize> {
+    match len {
+        Some(len) if len <= MAX_LEN => Ok(len),
+        _ => Err(Error::new(
+            span,
+            format!("the result would be longer than {MAX_LEN} items"),
+        )),
+    }
+}
+
+/// `start..end` counts down when `start` is greater than `end`, so `5..0` is `[5, 4, 3, 2, 1]`.
+fn int_range(start: i128, end: i128, inclusive: bool, span: Span) -> syn::Result<Value<'static>> {
+    let len = start.abs_diff(end).checked_add(u128::from(inclusive));
+    checked_len(len.and_then(|len| usize::try_from(len).ok()), span)?;
+
+    let items: Box<dyn Iterator<Item = i128>> = match (start <= end, inclusive) {
+        (true, false) => Box::new(start..end),
+        (true, true) => Box::new(start..=end),
+        (false, false) => Box::new((end + 1..=start).rev()),
+        (false, true) => Box::new((end..=start).rev()),
+    };
+
+    Ok(Value::List(ValueList {
+        span,
+        items: items.map(|i| ValueRef::Owned(int(i, span))).collect(),
+    }))
+}
+
+/// A repeat count, which has to fit in a `usize`.
+fn count(lit: &LitInt) -> syn::Result<usize> {
+    usize::try_from(lit.base10_parse::<i128>()?)
+        .map_err(|_| Error::new_spanned(lit, format!("expected a non-negative int, found `{lit}`")))
 }
 
-fn bool(value: bool, span: Span) -> Value<'static> {
+pub(super) fn bool(value: bool, span: Span) -> Value<'static> {
     Value::Bool(LitBool { value, span })
 }
 
-fn string(value: impl AsRef<str>, span: Span) -> Value<'static> {
+pub(super) fn string(value: impl AsRef<str>, span: Span) -> Value<'static> {
     Value::Str(LitStr::new(value.as_ref(), span))
 }
 
-fn ident(value: impl AsRef<str>, span: Span) -> Value<'static> {
-    Value::Ident(IdentStr::new(value.as_ref(), span))
+pub(super) fn char(value: char, span: Span) -> Value<'static> {
+    Value::Char(LitChar::new(value, span))
+}
+
+pub(super) fn ident(value: impl AsRef<str>, span: Span) -> syn::Result<Value<'static>> {
+    Ok(Value::Ident(IdentStr::new(value.as_ref(), span)?))
 }
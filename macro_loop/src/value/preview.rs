@@ -0,0 +1,80 @@
+use quote::ToTokens;
+
+use super::*;
+
+/// The most chars of a preview, after which it is cut off with `...`.
+const MAX_PREVIEW_LEN: usize = 40;
+
+/// The most list items of a preview, after which the rest is `...`.
+const MAX_PREVIEW_ITEMS: usize = 8;
+
+impl Value<'_> {
+    /// The name of the value's kind, as used in error messages.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Int(_) => "int",
+            Self::Float(_) => "float",
+            Self::Str(_) => "string",
+            Self::Char(_) => "char",
+            Self::CStr(_) => "c string",
+            Self::ByteStr(_) => "byte string",
+            Self::Ident(_) => "ident",
+            Self::List(_) => "list",
+            Self::Closure(_) => "closure",
+        }
+    }
+
+    /// A short rendering of the value, like `[x, y, z]` or `"text"`.
+    pub fn preview(&self) -> String {
+        let preview = self.render_items(MAX_PREVIEW_ITEMS);
+
+        match preview.char_indices().nth(MAX_PREVIEW_LEN) {
+            Some((idx, _)) => format!("{}...", &preview[..idx]),
+            None => preview,
+        }
+    }
+
+    /// The full rendering of the value, which `preview` shortens.
+    pub fn render(&self) -> String {
+        self.render_items(usize::MAX)
+    }
+
+    fn render_items(&self, max_items: usize) -> String {
+        match self {
+            Self::Bool(lit) => lit.value.to_string(),
+            Self::Str(lit) => format!("{:?}", lit.value()),
+            Self::Char(lit) => format!("{:?}", lit.value()),
+            Self::Ident(ident) => ident.str().to_string(),
+
+            Self::List(list) => {
+                let mut items = list
+                    .items
+                    .iter()
+                    .take(max_items)
+                    .map(|item| item.render_items(max_items))
+                    .collect::<Vec<_>>();
+
+                if list.items.len() > max_items {
+                    items.push("...".to_string());
+                }
+
+                format!("[{}]", items.join(", "))
+            }
+
+            Self::Int(_) | Self::Float(_) | Self::CStr(_) | Self::ByteStr(_) | Self::Closure(_) => {
+                self.to_token_stream().to_string()
+            }
+        }
+    }
+
+    /// The kind and preview of the value, like ``ident `x` ``.
+    pub fn describe(&self) -> String {
+        format!("{} `{}`", self.kind_name(), self.preview())
+    }
+
+    /// A type error message, like ``expected a list, found ident `x` ``.
+    pub fn expected(&self, expected: &str) -> String {
+        format!("expected {expected}, found {}", self.describe())
+    }
+}
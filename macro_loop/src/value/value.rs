@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
 use syn::{
@@ -19,6 +21,7 @@ pub enum Value<'v> {
     Ident(IdentStr),
 
     List(ValueList<'v>),
+    Closure(Rc<Closure>),
 }
 
 impl<'a> ToTokens for Value<'a> {
@@ -33,6 +36,7 @@ impl<'a> ToTokens for Value<'a> {
             Self::ByteStr(self_) => self_.to_tokens(tokens),
             Self::Ident(self_) => self_.to_tokens(tokens),
             Self::List(self_) => self_.to_tokens(tokens),
+            Self::Closure(self_) => self_.to_tokens(tokens),
         }
     }
 }
@@ -49,6 +53,7 @@ impl<'a> ToTokensSpanned for Value<'a> {
 
         match self {
             Self::List(list) => list.to_tokens_spanned(span, tokens),
+            Self::Closure(closure) => closure.to_tokens(tokens),
 
             Self::Bool(self_) => token_output!(self_),
             Self::Int(self_) => token_output!(self_),
@@ -69,6 +74,11 @@ impl<'v> Value<'v> {
 
             Expr::Value(value) => ValueRef::Ref(value),
 
+            Expr::Closure(closure) => ValueRef::Owned(Self::Closure(Rc::new(Closure::new(
+                closure.clone(),
+                namespace,
+            )))),
+
             Expr::List(list) => ValueRef::Owned(Self::List(ValueList {
                 span: list.span,
                 items: list
@@ -103,10 +113,24 @@ impl<'v> Value<'v> {
                     .map(|input| Value::from_expr(input, namespace))
                     .collect::<syn::Result<Vec<_>>>()?;
 
-                base.method(&expr.method, &inputs)?
+                base.method(&expr.method, &inputs, namespace)?
+            }
+
+            Expr::Call(expr) => {
+                let inputs = expr
+                    .inputs
+                    .iter()
+                    .map(|input| Value::from_expr(input, namespace))
+                    .collect::<syn::Result<Vec<_>>>()?;
+
+                ValueRef::call(&expr.func, &inputs, namespace)?
             }
 
             Expr::Frag(ExprFrag { _at_token: _, frag }) => {
+                if let Some(closure) = Self::named_closure(frag, namespace)? {
+                    return Ok(ValueRef::Owned(closure));
+                }
+
                 let expr = {
                     let mut namespace = namespace.fork();
 
@@ -116,7 +140,7 @@ impl<'v> Value<'v> {
                     Expr::parse.parse2(output)?
                 };
 
-                Value::from_owned_expr(expr, &namespace)?
+                Value::from_owned_expr(expr, namespace)?
             }
         })
     }
@@ -127,6 +151,10 @@ impl<'v> Value<'v> {
 
             Expr::Value(value) => ValueRef::Owned(value),
 
+            Expr::Closure(closure) => {
+                ValueRef::Owned(Self::Closure(Rc::new(Closure::new(closure, namespace))))
+            }
+
             Expr::List(list) => ValueRef::Owned(Self::List(ValueList {
                 span: list.span,
                 items: list
@@ -162,10 +190,24 @@ impl<'v> Value<'v> {
                     .map(|input| Value::from_owned_expr(input, namespace))
                     .collect::<syn::Result<Vec<_>>>()?;
 
-                base.method(&expr.method, &inputs)?
+                base.method(&expr.method, &inputs, namespace)?
+            }
+
+            Expr::Call(expr) => {
+                let inputs = expr
+                    .inputs
+                    .into_iter()
+                    .map(|input| Value::from_owned_expr(input, namespace))
+                    .collect::<syn::Result<Vec<_>>>()?;
+
+                ValueRef::call(&expr.func, &inputs, namespace)?
             }
 
             Expr::Frag(ExprFrag { _at_token: _, frag }) => {
+                if let Some(closure) = Self::named_closure(&frag, namespace)? {
+                    return Ok(ValueRef::Owned(closure));
+                }
+
                 let expr = {
                     let mut namespace = namespace.fork();
 
@@ -175,11 +217,50 @@ impl<'v> Value<'v> {
                     Expr::parse.parse2(output)?
                 };
 
-                Value::from_owned_expr(expr, &namespace)?
+                Value::from_owned_expr(expr, namespace)?
             }
         })
     }
 
+    /// The closure that a `@name` fragment refers to, if it is one.
+    ///
+    /// Other values are parsed back from the fragment's output,
+    /// but a closure would lose its captures that way.
+    fn named_closure(frag: &Frag, namespace: &Namespace) -> syn::Result<Option<Self>> {
+        let Frag::Name(frag) = frag else {
+            return Ok(None);
+        };
+
+        Ok(match namespace.get(frag.name())? {
+            Value::Closure(closure) => Some(Value::Closure(closure.clone())),
+            _ => None,
+        })
+    }
+
+    /// Copies the value out of everything it borrows.
+    pub fn to_static(&self) -> Value<'static> {
+        match self {
+            Self::Bool(self_) => Value::Bool(self_.clone()),
+            Self::Int(self_) => Value::Int(self_.clone()),
+            Self::Float(self_) => Value::Float(self_.clone()),
+            Self::Str(self_) => Value::Str(self_.clone()),
+            Self::Char(self_) => Value::Char(self_.clone()),
+            Self::CStr(self_) => Value::CStr(self_.clone()),
+            Self::ByteStr(self_) => Value::ByteStr(self_.clone()),
+            Self::Ident(self_) => Value::Ident(self_.clone()),
+            Self::Closure(self_) => Value::Closure(self_.clone()),
+
+            Self::List(self_) => Value::List(ValueList {
+                span: self_.span,
+                items: self_
+                    .items
+                    .iter()
+                    .map(|item| ValueRef::Owned(item.to_static()))
+                    .collect(),
+            }),
+        }
+    }
+
     pub fn from_lit(lit: Lit) -> syn::Result<Self> {
         Ok(match lit {
             Lit::Bool(lit) => Self::Bool(lit),
@@ -199,11 +280,17 @@ impl<'v> Value<'v> {
     pub fn try_to_string(&self) -> syn::Result<String> {
         Ok(match self {
             Self::Bool(lit) => lit.value.to_string(),
-            Self::Int(lit) => lit.base10_parse::<u128>().unwrap().to_string(),
+            Self::Int(lit) => lit.base10_parse::<i128>()?.to_string(),
             Self::Str(lit) => lit.value(),
             Self::Char(lit) => lit.value().to_string(),
-            Self::CStr(lit) => lit.value().to_str().unwrap().to_string(),
-            Self::ByteStr(lit) => String::from_utf8(lit.value()).unwrap(),
+            Self::CStr(lit) => match lit.value().into_string() {
+                Ok(str) => str,
+                Err(_) => return Err(Error::new_spanned(lit, "c string is not valid UTF-8")),
+            },
+            Self::ByteStr(lit) => match String::from_utf8(lit.value()) {
+                Ok(str) => str,
+                Err(_) => return Err(Error::new_spanned(lit, "byte string is not valid UTF-8")),
+            },
             Self::Ident(ident) => ident.str().to_string(),
 
             Self::List(list) => list
@@ -213,7 +300,10 @@ impl<'v> Value<'v> {
                 .collect::<syn::Result<String>>()?,
 
             _ => {
-                return Err(Error::new_spanned(self, "not an identifier value"));
+                return Err(Error::new_spanned(
+                    self,
+                    format!("{} cannot be part of an identifier", self.describe()),
+                ));
             }
         })
     }
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
 use quote::{ToTokens, TokenStreamExt};
 use syn::{Error, Token, punctuated::Punctuated, spanned::Spanned};
@@ -40,7 +42,7 @@ impl<'v> ToTokensSpanned for ValueList<'v> {
 // Index
 
 impl<'v> ValueRef<'v> {
-    pub fn index_cloned(&self, idx: usize, span: Span) -> syn::Result<ValueRef<'v>> {
+    pub fn index_cloned(&self, idx: i128, span: Span) -> syn::Result<ValueRef<'v>> {
         Ok(match self {
             Self::Owned(Value::List(self_)) => self_.index_cloned(idx, span)?,
 
@@ -51,7 +53,7 @@ impl<'v> ValueRef<'v> {
     }
 
     #[allow(dead_code)]
-    pub fn index_ref(&'v self, idx: usize, span: Span) -> syn::Result<&'v ValueRef<'v>> {
+    pub fn index_ref(&'v self, idx: i128, span: Span) -> syn::Result<&'v ValueRef<'v>> {
         Ok(match self {
             Self::Owned(Value::List(self_)) | Self::Ref(Value::List(self_)) => {
                 self_.index_ref(idx, span)?
@@ -60,20 +62,69 @@ impl<'v> ValueRef<'v> {
             _ => return Err(Error::new(span, "non-lists cannot be indexed")),
         })
     }
+
+    pub fn slice_cloned(&self, start: i128, end: i128, span: Span) -> syn::Result<ValueRef<'v>> {
+        Ok(match self {
+            Self::Owned(Value::List(self_)) => {
+                let range = self_.slice_range(start, end, span)?;
+
+                ValueRef::Owned(Value::List(ValueList {
+                    span: self_.span,
+                    items: self_.items[range].to_vec(),
+                }))
+            }
+
+            Self::Ref(Value::List(self_)) => {
+                let range = self_.slice_range(start, end, span)?;
+
+                ValueRef::Owned(Value::List(ValueList {
+                    span: self_.span,
+                    items: self_.items[range]
+                        .iter()
+                        .map(|item| ValueRef::Ref(item))
+                        .collect(),
+                }))
+            }
+
+            _ => return Err(Error::new(span, "non-lists cannot be sliced")),
+        })
+    }
 }
 
 impl<'v> ValueList<'v> {
-    pub fn index_cloned(&self, idx: usize, span: Span) -> syn::Result<ValueRef<'v>> {
-        match self.items.get(idx) {
-            Some(item) => Ok(item.clone()),
-            None => Err(Error::new(span, "index is out of bounds")),
+    pub fn index_cloned(&self, idx: i128, span: Span) -> syn::Result<ValueRef<'v>> {
+        Ok(self.items[self.resolve_idx(idx, span)?].clone())
+    }
+
+    pub fn index_ref(&'v self, idx: i128, span: Span) -> syn::Result<&'v ValueRef<'v>> {
+        Ok(&self.items[self.resolve_idx(idx, span)?])
+    }
+
+    /// Resolves a possibly negative index, where `-1` is the last item.
+    fn resolve_idx(&self, idx: i128, span: Span) -> syn::Result<usize> {
+        let len = self.items.len() as i128;
+        let resolved = if idx < 0 { len + idx } else { idx };
+
+        if (0..len).contains(&resolved) {
+            Ok(resolved as usize)
+        } else {
+            Err(Error::new(span, "index is out of bounds"))
         }
     }
 
-    pub fn index_ref(&'v self, idx: usize, span: Span) -> syn::Result<&'v ValueRef<'v>> {
-        match self.items.get(idx) {
-            Some(item) => Ok(item),
-            None => Err(Error::new(span, "index is out of bounds")),
+    /// Resolves the item range of `start..end`, where negative bounds count from the end.
+    fn slice_range(&self, start: i128, end: i128, span: Span) -> syn::Result<Range<usize>> {
+        let len = self.items.len() as i128;
+        let start = if start < 0 { len + start } else { start };
+        let end = if end < 0 { len + end } else { end };
+
+        if start < 0 || end > len {
+            return Err(Error::new(span, "slice is out of bounds"));
         }
+        if start > end {
+            return Err(Error::new(span, "slice starts after its end"));
+        }
+
+        Ok(start as usize..end as usize)
     }
 }
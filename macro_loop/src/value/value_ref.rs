@@ -24,20 +24,20 @@ impl<'v> Deref for ValueRef<'v> {
 
 impl<'v> ToTokens for ValueRef<'v> {
     fn to_token_stream(&self) -> TokenStream {
-        Value::to_token_stream(&self)
+        Value::to_token_stream(self)
     }
 
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        Value::to_tokens(&self, tokens);
+        Value::to_tokens(self, tokens);
     }
 }
 
 impl<'v> ToTokensSpanned for ValueRef<'v> {
     fn to_token_stream_spanned(&self, span: Span) -> TokenStream {
-        Value::to_token_stream_spanned(&self, span)
+        Value::to_token_stream_spanned(self, span)
     }
 
     fn to_tokens_spanned(&self, span: Span, tokens: &mut TokenStream) {
-        Value::to_tokens_spanned(&self, span, tokens);
+        Value::to_tokens_spanned(self, span, tokens);
     }
 }
@@ -0,0 +1,180 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+
+use crate::{
+    macro_loop2,
+    name::warn_unused_bindings,
+    util::{emit_warnings, take_warnings},
+};
+
+#[test]
+fn test_unused_bindings() {
+    assert_eq!(
+        warnings(quote! { @let x = 1; @let [a, _b] = [1, 2]; @a }),
+        ["unused binding `x`. if this is intentional, prefix it with an underscore: `_x`"]
+    );
+    assert_eq!(
+        warnings(quote! { @for i in 0..3, _j in 0..3 {} }),
+        ["unused binding `i`. if this is intentional, prefix it with an underscore: `_i`"]
+    );
+    assert_eq!(
+        warnings(quote! { @let x = 1; @"{@x}" @([1].map(|y| 0)) }),
+        ["unused binding `y`. if this is intentional, prefix it with an underscore: `_y`"]
+    );
+    assert!(warnings(quote! { @let N = 2; @(format("{N}")) }).is_empty());
+
+    // an expansion that stops before reporting its bindings doesn't leak them into the next one
+    macro_loop2.parse2(quote! { @let x = 1; }).unwrap();
+    assert!(warnings(quote! { @let _x = 1; }).is_empty());
+}
+
+#[test]
+fn test_shadowed_bindings() {
+    assert_eq!(
+        warnings(quote! { @for x in [1] { @for x in [2] { @x } } }),
+        [
+            "`x` shadows a binding of the same name from an outer scope",
+            "unused binding `x`. if this is intentional, prefix it with an underscore: `_x`"
+        ]
+    );
+    assert!(warnings(quote! { @for _x in [1] { @for _x in [2] {} } }).is_empty());
+}
+
+#[test]
+fn test_fragment_names() {
+    assert_eq!(
+        warnings(quote! { @for trace in [1] { @trace } }),
+        ["`trace` is bound, but `@trace { ... }` still traces its body instead of reading it"]
+    );
+    assert_eq!(
+        warnings(quote! { @let [x, dbg] = [1, 2]; @x @dbg }),
+        ["`dbg` is bound, but `@dbg(...)` still debugs its input instead of reading it"]
+    );
+}
+
+#[test]
+fn test_closure_bindings() {
+    // the closure reads the `k` where it is written, not the `k` where it is called
+    assert_eq!(
+        warnings(quote! {
+            @let k = 1;
+            @let f = |x| @x + @k;
+
+            @for k in [10] {
+                @([1].map(@f))
+            }
+        }),
+        [
+            "`k` shadows a binding of the same name from an outer scope",
+            "unused binding `k`. if this is intentional, prefix it with an underscore: `_k`"
+        ]
+    );
+
+    // a closure's parameters shadow the names where it is written
+    assert_eq!(
+        warnings(quote! { @let x = 1; @([1].map(|x| @x)) }),
+        [
+            "`x` shadows a binding of the same name from an outer scope",
+            "unused binding `x`. if this is intentional, prefix it with an underscore: `_x`"
+        ]
+    );
+
+    // capturing a name uses it, even if the closure is never called
+    assert_eq!(
+        warnings(quote! { @let k = 1; @let f = |x| @x + @k; @([].map(@f)) }),
+        Vec::<String>::new()
+    );
+
+    // a closure reads `@name` and placeholders, not names that a literal happens to contain
+    assert_eq!(
+        warnings(quote! { @let i = 1; @let x = 2; @([1].map(|_y| "index")) }),
+        [
+            "unused binding `i`. if this is intentional, prefix it with an underscore: `_i`",
+            "unused binding `x`. if this is intentional, prefix it with an underscore: `_x`"
+        ]
+    );
+    assert!(warnings(quote! { @let i = 1; @([1].map(|_y| "{@i}")) }).is_empty());
+    assert!(warnings(quote! { @let w = 4; @([1].map(|y| format("{y:w$}"))) }).is_empty());
+}
+
+#[test]
+fn test_warning_placement() {
+    // next to items and statements
+    assert_eq!(
+        place(quote! { @let x = 1; struct A; }),
+        "struct A ; const _ : () = { .. } ;"
+    );
+    assert_eq!(
+        place(quote! { @let x = 1; let y = 2; }),
+        "let y = 2 ; const _ : () = { .. } ;"
+    );
+
+    // `impl` and `trait` items can't contain `const _`, but `fn` bodies can
+    assert_eq!(
+        place(quote! { @let x = 1; fn f() -> u8 { 0 } }),
+        "fn f () -> u8 { const _ : () = { .. } ; 0 }"
+    );
+    assert_eq!(
+        place(quote! { @let x = 1; m!(fn g() {}); fn f() {} }),
+        "m ! (fn g () { }) ; fn f () { const _ : () = { .. } ; }"
+    );
+
+    // a block around an expression, unless it is also a type or a pattern, or a place
+    assert_eq!(
+        place(quote! { @let x = 1; 1 + 2 }),
+        "{ const _ : () = { .. } ; 1 + 2 }"
+    );
+    assert_eq!(place(quote! { @let x = 1; u8 }), "u8");
+    assert_eq!(place(quote! { @let x = 1; 3 }), "3");
+    assert_eq!(place(quote! { @let x = 1; self.x }), "self . x");
+
+    // requested output isn't dropped
+    assert_eq!(
+        place(quote! { @dbg(1) u8 }),
+        "compile_error ! (\"warnings can't be placed in this position without the `nightly` feature, \
+        so the output of `@dbg` and `@trace` is reported as an error. \
+        move them to a call that outputs items, statements, a `fn` or an expression:\\n`1` = int `1`\")"
+    );
+}
+
+/// Expands a template and returns its warnings, in the order they are reported.
+fn warnings(template: TokenStream) -> Vec<String> {
+    take_warnings();
+
+    if let Err(err) = macro_loop2.parse2(template.clone()) {
+        panic!("`{template}`: {err}");
+    }
+    warn_unused_bindings();
+
+    take_warnings()
+        .into_iter()
+        .map(|warning| warning.message)
+        .collect()
+}
+
+/// Expands a template with its warnings placed in the output,
+/// where the contents of a warning's `const _` are written as `..`.
+fn place(template: TokenStream) -> String {
+    take_warnings();
+
+    let output = match macro_loop2.parse2(template.clone()) {
+        Ok(output) => output,
+        Err(err) => panic!("`{template}`: {err}"),
+    };
+    warn_unused_bindings();
+
+    let mut output = emit_warnings(output, take_warnings()).to_string();
+
+    let item_start = "const _ : () = {";
+    let mut search_start = 0;
+    while let Some(start) = output[search_start..].find(item_start) {
+        let contents_start = search_start + start + item_start.len();
+        let contents_end = contents_start + output[contents_start..].find('}').unwrap();
+
+        output.replace_range(contents_start..contents_end, " .. ");
+        search_start = contents_start;
+    }
+
+    output
+}
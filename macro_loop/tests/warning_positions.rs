@@ -0,0 +1,90 @@
+//! Warnings must never change whether a call compiles,
+//! so these calls report warnings (unused and shadowing bindings, and a `@dbg`) in every position.
+//! On stable, the warnings are deprecation warnings, which are allowed to build with `-D warnings`.
+
+#![allow(deprecated)]
+
+use macro_loop::macro_loop;
+
+macro_loop! {
+    @let unused = 1;
+    @let used = 2;
+    @dbg(@used)
+
+    struct Item;
+}
+
+mod positions {
+    use super::*;
+
+    pub struct Vec2;
+
+    impl Vec2 {
+        macro_loop! {
+            @let unused = 1;
+
+            @for X in [x, y] {
+                @for X in [@X] {}
+
+                pub fn @X(&self) -> u8 {
+                    0
+                }
+            }
+        }
+    }
+
+    pub trait Named {
+        macro_loop! {
+            @let unused = 1;
+
+            fn name(&self) -> &'static str;
+        }
+    }
+
+    impl Named for Vec2 {
+        fn name(&self) -> &'static str {
+            "Vec2"
+        }
+    }
+
+    pub fn expression() -> u8 {
+        macro_loop! {
+            @let unused = 1;
+            @let used = 2;
+            @dbg(@used)
+            @for used in [@used] {}
+
+            1 + @used
+        }
+    }
+}
+
+#[test]
+fn test_statement_position() {
+    macro_loop! {
+        @let unused = 1;
+
+        let x = 3;
+    }
+
+    assert_eq!(x, 3);
+    let _ = Item;
+}
+
+#[test]
+fn test_type_and_pattern_positions() {
+    let x: macro_loop! { @let unused = 1; u8 } = 3;
+
+    match x {
+        macro_loop! { @let unused = 1; 3 } => {}
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_expression_impl_and_trait_positions() {
+    use positions::*;
+
+    assert_eq!(expression(), 3);
+    assert_eq!((Vec2.x(), Vec2.y(), Vec2.name()), (0, 0, "Vec2"));
+}